@@ -0,0 +1,120 @@
+import * as anchor from '@coral-xyz/anchor';
+import { PublicKey } from '@solana/web3.js';
+
+// Sizes of the account types that were deployed before the version header existed.
+// An account of one of these sizes is read as version 0.
+const LEGACY_SIZES: Record<string, number> = {
+  User: 48,
+  Jackpot: 48,
+  StakingTreasury: 8,
+  TotalWonPoints: 16,
+  Treasury: 16,
+  PlayerList: 680,
+  Leaderboard: 4008,
+};
+
+// PlayerList was a Borsh Vec<Pubkey> before the ring buffer: unversioned it starts after the
+// discriminator, at version 1 after the header byte. Keyed by account size.
+const VEC_PLAYER_LIST_OFFSETS: Record<number, number> = { 680: 8, 333: 9 };
+const PLAYER_LIST_CAPACITY = 32;
+const DEFAULT_PLAYER_WINDOW = 10;
+const WIN_HISTORY_CAPACITY = 10;
+
+// Sizes of versioned layouts that later versions only appended fields to, with the version they were at
+const GROWN_SIZES: Record<string, Record<number, number>> = {
+  User: { 121: 1, 195: 2, 203: 3 },
+};
+
+export type VersionedAccount<T> = {
+  version: number;
+  // Decoded from a superseded layout, the program only accepts it after migrate_account
+  outdated: boolean;
+  account: T;
+};
+
+// Decode any version of a program account, `name` is the account type as in the IDL (e.g. "User")
+export function decodeAccount<T = any>(program: anchor.Program<any>, name: string, data: Buffer): VersionedAccount<T> {
+  if (name === "PlayerList" && VEC_PLAYER_LIST_OFFSETS[data.length] !== undefined) {
+    return decodeVecPlayerList(data) as VersionedAccount<T>;
+  }
+
+  const grownFrom = GROWN_SIZES[name]?.[data.length];
+  if (grownFrom !== undefined) {
+    const clientName = name.charAt(0).toLowerCase() + name.slice(1);
+    const upgraded = Buffer.alloc(program.account[clientName].size);
+    data.copy(upgraded);
+    return { version: grownFrom, outdated: true, account: program.coder.accounts.decode(name, upgraded) };
+  }
+
+  if (data.length !== LEGACY_SIZES[name]) {
+    const account = program.coder.accounts.decode(name, data);
+    return { version: account.version, outdated: false, account };
+  }
+
+  // Same upgrade migrate_account does on chain: header byte after the discriminator, appended fields zeroed
+  const clientName = name.charAt(0).toLowerCase() + name.slice(1);
+  const size = Math.max(program.account[clientName].size, data.length + 1);
+  const upgraded = Buffer.alloc(size);
+  data.copy(upgraded, 0, 0, 8);
+  data.copy(upgraded, 9, 8);
+  const account = program.coder.accounts.decode(name, upgraded);
+  return { version: 0, outdated: true, account };
+}
+
+export async function fetchAccount<T = any>(program: anchor.Program<any>, name: string, address: PublicKey): Promise<VersionedAccount<T>> {
+  const info = await program.provider.connection.getAccountInfo(address);
+  if (!info) {
+    throw new Error(`Account ${address.toBase58()} does not exist`);
+  }
+  return decodeAccount<T>(program, name, info.data);
+}
+
+// Older layouts can still be read here, but the program only accepts them after migrate_account
+export function needsMigration(versioned: VersionedAccount<any>): boolean {
+  return versioned.outdated;
+}
+
+// Same number the program derives from a player's pubkey
+export function pubkeyToNumber(pubkey: PublicKey): number {
+  return (pubkey.toBytes().reduce((sum, byte) => sum + byte, 0) % 100_000) + 1;
+}
+
+// Entries of a decoded PlayerList that count towards its window sum, newest first
+export function recentPlayers(playerList: any): any[] {
+  const inWindow = Math.min(playerList.count, playerList.window);
+  return Array.from({ length: inWindow }, (_, back) =>
+    playerList.entries[(playerList.head + PLAYER_LIST_CAPACITY - 1 - back) % PLAYER_LIST_CAPACITY]);
+}
+
+// Wins recorded in a decoded WinHistory, newest first
+export function recentWins(winHistory: any): any[] {
+  return Array.from({ length: winHistory.count }, (_, back) =>
+    winHistory.entries[(winHistory.head + WIN_HISTORY_CAPACITY - 1 - back) % WIN_HISTORY_CAPACITY]);
+}
+
+// Replays a Vec player list into the ring buffer shape, as migrate_account does on chain
+function decodeVecPlayerList(data: Buffer): VersionedAccount<any> {
+  const offset = VEC_PLAYER_LIST_OFFSETS[data.length];
+  const version = offset === 8 ? 0 : data[8];
+  const players: PublicKey[] = [];
+  for (let i = 0; i < data.readUInt32LE(offset); i++) {
+    players.push(new PublicKey(data.subarray(offset + 4 + 32 * i, offset + 36 + 32 * i)));
+  }
+
+  const empty = { player: PublicKey.default, slot: new anchor.BN(0), number: new anchor.BN(0) };
+  const account = {
+    version,
+    head: players.length % PLAYER_LIST_CAPACITY,
+    count: Math.min(players.length, PLAYER_LIST_CAPACITY),
+    window: DEFAULT_PLAYER_WINDOW,
+    admin: PublicKey.default,
+    windowSum: new anchor.BN(0),
+    entries: Array.from({ length: PLAYER_LIST_CAPACITY }, () => empty),
+  };
+  players.forEach((player, i) => {
+    account.entries[i % PLAYER_LIST_CAPACITY] = { player, slot: new anchor.BN(0), number: new anchor.BN(pubkeyToNumber(player)) };
+  });
+  const windowSum = recentPlayers(account).reduce((sum, entry) => sum + entry.number.toNumber(), 0);
+  account.windowSum = new anchor.BN(windowSum);
+  return { version, outdated: true, account };
+}
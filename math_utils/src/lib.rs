@@ -1,6 +1,10 @@
 // math_utils/src/lib.rs
 
+// `u64::is_multiple_of` is newer than the rustc shipped with the SBF platform tools.
+#![allow(clippy::manual_is_multiple_of)]
+
 use anchor_lang::prelude::SolanaSysvar;
+use anchor_lang::solana_program::hash::hashv;
 
 pub fn is_prime(n: u64, k: u32) -> bool {
     if n <= 1 {
@@ -51,16 +55,174 @@ pub fn miller_rabin_test(d: u64, n: u64) -> bool {
     false
 }
 
+// Witnesses that make Miller-Rabin exact for every u64
+pub const DETERMINISTIC_WITNESSES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+// Exact primality for any u64, for checks whose outcome must not depend on the Clock sysvar
+pub fn is_prime_deterministic(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for p in DETERMINISTIC_WITNESSES {
+        if n % p == 0 {
+            return n == p;
+        }
+    }
+
+    let mut d = n - 1;
+    let mut r = 0;
+    while d % 2 == 0 {
+        d /= 2;
+        r += 1;
+    }
+
+    'witness: for a in DETERMINISTIC_WITNESSES {
+        let mut x = mod_exp(a, d, n);
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        for _ in 1..r {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
 pub fn mod_exp(mut base: u64, mut exp: u64, modulus: u64) -> u64 {
     let mut result = 1;
-    base = base % modulus;
+    base %= modulus;
     while exp > 0 {
         if exp % 2 == 1 {
-            result = (result * base) % modulus;
+            result = mul_mod(result, base, modulus);
         }
-        exp = exp >> 1;
-        base = (base * base) % modulus;
+        exp >>= 1;
+        base = mul_mod(base, base, modulus);
     }
     result
 }
 
+// Widen to u128 so products of residues above 2^32 can't overflow
+pub fn mul_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    ((a as u128 * b as u128) % modulus as u128) as u64
+}
+
+// Candidate a miner proposes in proof-of-search mode: H(slot_hash || pubkey || nonce) as a u64
+pub fn search_candidate(slot_hash: &[u8; 32], pubkey: &[u8; 32], nonce: u64) -> u64 {
+    let hash = hashv(&[slot_hash, pubkey, &nonce.to_le_bytes()]);
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&hash.to_bytes()[..8]);
+    u64::from_le_bytes(bytes)
+}
+
+// True if the last `digits` decimal digits of `n` equal `suffix`
+pub fn has_suffix(n: u64, suffix: u64, digits: u8) -> bool {
+    match 10u64.checked_pow(digits as u32) {
+        Some(modulus) => n % modulus == suffix,
+        None => false,
+    }
+}
+
+
+// Extra win filter scaled by difficulty: roughly 1 in `difficulty` candidates pass
+pub fn meets_difficulty(n: u64, difficulty: u64) -> bool {
+    difficulty <= 1 || mix64(n) % difficulty == 0
+}
+
+// splitmix64 finalizer, spreads consecutive candidates across the whole u64 range
+pub fn mix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+// Next difficulty after `wins` took `actual_slots` instead of `wins * target_win_interval`.
+// Like Bitcoin, a single retarget moves the difficulty by at most a factor of 4.
+pub fn retarget_difficulty(difficulty: u64, actual_slots: u64, target_win_interval: u64, wins: u64) -> u64 {
+    let expected_slots = target_win_interval.saturating_mul(wins) as u128;
+    let actual_slots = (actual_slots as u128).clamp(expected_slots / 4, expected_slots * 4).max(1);
+    let next = difficulty as u128 * expected_slots / actual_slots;
+    next.clamp(1, u64::MAX as u128) as u64
+}
+
+// Compute-unit budgets for the primitives above. SBF has no 128-bit divide, so the `%` in mul_mod
+// is a compiler-builtins software division costing hundreds of units, not a single instruction.
+// check_slots only relies on these never under-counting; tests/check_slots.ts runs a batch at the
+// largest size they allow and logs what it really consumed, retune from that with margin.
+pub const MUL_MOD_CU: u64 = 400;
+pub const MILLER_RABIN_ROUND_OVERHEAD_CU: u64 = 500; // Clock sysvar syscall + loop setup
+pub const IS_PRIME_OVERHEAD_CU: u64 = 100;
+
+// Upper-bound estimate of the compute units `is_prime(n, k)` can consume
+pub fn estimate_is_prime_cu(n: u64, k: u32) -> u64 {
+    let bits = (64 - n.leading_zeros()) as u64;
+    // mod_exp squares and may multiply once per bit, then the witness loop squares up to once per bit
+    let per_round = MILLER_RABIN_ROUND_OVERHEAD_CU + 3 * bits * MUL_MOD_CU;
+    IS_PRIME_OVERHEAD_CU + k as u64 * per_round
+}
+
+// Number of `is_prime(n, k)` calls that fit in a compute budget
+pub fn max_prime_tests_within_budget(budget: u64, n: u64, k: u32) -> u64 {
+    budget / estimate_is_prime_cu(n, k)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimate_is_prime_cu_counts_three_mul_mods_per_bit_and_round() {
+        // 1_000_003 is 20 bits wide
+        let per_round = MILLER_RABIN_ROUND_OVERHEAD_CU + 3 * 20 * MUL_MOD_CU;
+        assert_eq!(estimate_is_prime_cu(1_000_003, 5), IS_PRIME_OVERHEAD_CU + 5 * per_round);
+        assert_eq!(estimate_is_prime_cu(1_000_003, 0), IS_PRIME_OVERHEAD_CU);
+    }
+
+    #[test]
+    fn estimate_is_prime_cu_grows_with_bit_length_and_rounds() {
+        assert!(estimate_is_prime_cu(1 << 20, 5) < estimate_is_prime_cu(1 << 40, 5));
+        assert!(estimate_is_prime_cu(1 << 40, 5) < estimate_is_prime_cu(u64::MAX, 5));
+        assert!(estimate_is_prime_cu(1 << 40, 3) < estimate_is_prime_cu(1 << 40, 5));
+        // Numbers of the same width cost the same
+        assert_eq!(estimate_is_prime_cu(1 << 40, 5), estimate_is_prime_cu((1 << 41) - 1, 5));
+    }
+
+    #[test]
+    fn max_prime_tests_within_budget_divides_the_budget() {
+        let estimate = estimate_is_prime_cu(1 << 40, 5);
+        assert_eq!(max_prime_tests_within_budget(estimate * 7, 1 << 40, 5), 7);
+        assert_eq!(max_prime_tests_within_budget(estimate * 7 + estimate - 1, 1 << 40, 5), 7);
+        assert_eq!(max_prime_tests_within_budget(estimate - 1, 1 << 40, 5), 0);
+        assert_eq!(max_prime_tests_within_budget(0, 1 << 40, 5), 0);
+    }
+
+    #[test]
+    fn is_prime_deterministic_matches_trial_division() {
+        let trial = |n: u64| n >= 2 && (2..).take_while(|d| d * d <= n).all(|d| n % d != 0);
+        for n in 0..10_000 {
+            assert_eq!(is_prime_deterministic(n), trial(n), "{}", n);
+        }
+    }
+
+    #[test]
+    fn is_prime_deterministic_rejects_strong_pseudoprimes() {
+        // Strong pseudoprimes to every base below 29 and to the bases up to 7
+        assert!(!is_prime_deterministic(3_825_123_056_546_413_051));
+        assert!(!is_prime_deterministic(3_215_031_751));
+        assert!(!is_prime_deterministic(u64::MAX));
+        assert!(is_prime_deterministic(1_000_003));
+        assert!(is_prime_deterministic(18_446_744_073_709_551_557)); // largest u64 prime
+    }
+
+    #[test]
+    fn max_prime_tests_within_budget_never_exceeds_the_budget() {
+        for n in [5, 1_000_003, 1 << 33, 1 << 52, u64::MAX] {
+            let tests = max_prime_tests_within_budget(1_000_000, n, 5);
+            assert!(tests * estimate_is_prime_cu(n, 5) <= 1_000_000);
+            assert!((tests + 1) * estimate_is_prime_cu(n, 5) > 1_000_000);
+        }
+    }
+}
@@ -1,18 +1,157 @@
 use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use anchor_spl::associated_token::{self, AssociatedToken};
+use anchor_spl::token_2022::spl_token_2022::{self, extension::ExtensionType, instruction::AuthorityType};
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface::{self, Burn, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked};
 use std::vec::Vec;
-use math_utils::{is_prime};
+use math_utils::{has_suffix, is_prime, is_prime_deterministic, max_prime_tests_within_budget, meets_difficulty, retarget_difficulty, search_candidate};
 
 declare_id!("B4FMCpibTGdZhxHHNgWWnwk5PhhKdST37uFRY6TVksaj");
 
+// Most compute units a single transaction can request
+const MAX_TRANSACTION_CU: u64 = 1_400_000;
+// Everything check_slots spends besides primality tests: account loads and writes, yield and vesting
+// bookkeeping, a settled win's transfers and events, and the msg! logs
+const CHECK_SLOTS_FIXED_CU: u64 = 300_000;
+// Compute units a check_slots batch may spend on primality tests
+const CHECK_SLOTS_CU_BUDGET: u64 = MAX_TRANSACTION_CU - CHECK_SLOTS_FIXED_CU;
+// Spacing between the candidates of a check_slots batch, even so odd candidates stay odd
+const CANDIDATE_STRIDE: u64 = 7_918;
+// Points a round costs at the main table
+const ROUND_COST: i64 = 10;
+// Main table SuperPrimes end in 01 and win the whole treasury
+const SUPERPRIME_DIGITS: u8 = 2;
+const SUPERPRIME_SUFFIX: u64 = 1;
+// Fixed-point scale of YieldAccumulator.acc_reward_per_point
+const ACC_PRECISION: u128 = 1_000_000_000_000;
+// Lock-up tiers for staked won points: (minimum lock in slots, yield multiplier in bps)
+const STAKE_TIERS: [(u64, u64); 4] = [
+    (216_000, 11_000),     // ~1 day, 1.1x
+    (1_512_000, 12_500),   // ~1 week, 1.25x
+    (6_480_000, 15_000),   // ~30 days, 1.5x
+    (19_440_000, 20_000),  // ~90 days, 2x
+];
+// Slots between unstake_points and withdraw_stake
+const UNSTAKE_COOLDOWN_SLOTS: u64 = 216_000;
+// Guild members must stay this long before leaving
+const GUILD_LEAVE_COOLDOWN_SLOTS: u64 = 216_000;
+// Guilds kept on the guild leaderboard
+const MAX_GUILD_LEADERBOARD: usize = 10;
+// Roughly one day of slots, the window for the transfer cap
+const SLOTS_PER_DAY: u64 = 216_000;
+// Ceiling on the referrer's cut of a purchase
+const MAX_REFERRAL_BPS: u16 = 1000;
+// Ceiling on either protocol fee
+const MAX_FEE_BPS: u16 = 1000;
+// Share of SOL point purchases that goes to the treasury, the rest feeds the yield pool
+const TREASURY_BPS: u64 = 8_000;
+// Volume discount tiers a PricingConfig can hold
+const MAX_VOLUME_TIERS: usize = 4;
+// SPL mints pay_for_points_spl accepts
+const MAX_ACCEPTED_MINTS: usize = 8;
+// Entries a PlayerList ring buffer holds, the window can be resized up to this
+const PLAYER_LIST_CAPACITY: usize = 32;
+// Recent players summed into each round until the list's admin resizes the window
+const DEFAULT_PLAYER_WINDOW: u16 = 10;
+// Entries the legacy Leaderboard account has room for
+const MAX_LEADERBOARD_USERS: usize = 100;
+// Wins a WinHistory ring buffer keeps
+const WIN_HISTORY_CAPACITY: usize = 10;
+// WinEntry.kind values
+const PRIME_KIND_PRIME: u8 = 0;
+const PRIME_KIND_SUPERPRIME: u8 = 1;
+// Bit indices of User.achievements, also the badge an achievement mints
+const ACHIEVEMENT_FIRST_PRIME: u8 = 0;
+const ACHIEVEMENT_FIRST_SUPERPRIME: u8 = 1;
+const ACHIEVEMENT_WIN_STREAK: u8 = 2;
+const ACHIEVEMENT_TWIN_PRIME: u8 = 3;
+const ACHIEVEMENT_CENTURY: u8 = 4;
+const ACHIEVEMENT_COUNT: u8 = 5;
+// Consecutive winning plays for ACHIEVEMENT_WIN_STREAK
+const ACHIEVEMENT_STREAK: u64 = 10;
+// Rounds played for ACHIEVEMENT_CENTURY
+const ACHIEVEMENT_ROUNDS: u64 = 100;
+// Players a season ranks, and the most ranks its distribution can pay
+const MAX_SEASON_STANDINGS: usize = 10;
+// Cap on the slice of each purchase that funds the season prize pool
+const MAX_SEASON_POOL_BPS: u16 = 2000;
+// Distinct winners an epoch keeps for its draw
+const MAX_EPOCH_WINNERS: usize = 10;
+// Cap on the slice of the treasury each epoch draw can take
+const MAX_EPOCH_DRAW_BPS: u16 = 2000;
+// Share of a regular win's treasury payout a proof earns until the admin retunes it
+const DEFAULT_PROOF_PAYOUT_BPS: u16 = 1000;
+
+// Every account type with its current size and layout version, looked up by discriminator in migrate_account
+const ACCOUNT_LAYOUTS: [([u8; 8], usize, u8); 35] = [
+    (User::DISCRIMINATOR, User::LEN, User::VERSION),
+    (Jackpot::DISCRIMINATOR, Jackpot::LEN, Jackpot::VERSION),
+    (StakingTreasury::DISCRIMINATOR, StakingTreasury::LEN, StakingTreasury::VERSION),
+    (TotalWonPoints::DISCRIMINATOR, TotalWonPoints::LEN, TotalWonPoints::VERSION),
+    (Treasury::DISCRIMINATOR, Treasury::LEN, Treasury::VERSION),
+    (PlayerList::DISCRIMINATOR, PlayerList::LEN, PlayerList::VERSION),
+    (Leaderboard::DISCRIMINATOR, Leaderboard::LEN, Leaderboard::VERSION),
+    (YieldAccumulator::DISCRIMINATOR, YieldAccumulator::LEN, YieldAccumulator::VERSION),
+    (TransferConfig::DISCRIMINATOR, TransferConfig::LEN, TransferConfig::VERSION),
+    (ReferralVault::DISCRIMINATOR, ReferralVault::LEN, ReferralVault::VERSION),
+    (ReferrerStats::DISCRIMINATOR, ReferrerStats::LEN, ReferrerStats::VERSION),
+    (FeeVault::DISCRIMINATOR, FeeVault::LEN, FeeVault::VERSION),
+    (VestingConfig::DISCRIMINATOR, VestingConfig::LEN, VestingConfig::VERSION),
+    (Vesting::DISCRIMINATOR, Vesting::LEN, Vesting::VERSION),
+    (StakePosition::DISCRIMINATOR, StakePosition::LEN, StakePosition::VERSION),
+    (Difficulty::DISCRIMINATOR, Difficulty::LEN, Difficulty::VERSION),
+    (TokenMigration::DISCRIMINATOR, TokenMigration::LEN, TokenMigration::VERSION),
+    (PricingConfig::DISCRIMINATOR, PricingConfig::LEN, PricingConfig::VERSION),
+    (PaymentConfig::DISCRIMINATOR, PaymentConfig::LEN, PaymentConfig::VERSION),
+    (SplYieldPool::DISCRIMINATOR, SplYieldPool::LEN, SplYieldPool::VERSION),
+    (SplYieldPosition::DISCRIMINATOR, SplYieldPosition::LEN, SplYieldPosition::VERSION),
+    (Table::DISCRIMINATOR, Table::LEN, Table::VERSION),
+    (TableSeat::DISCRIMINATOR, TableSeat::LEN, TableSeat::VERSION),
+    (Guild::DISCRIMINATOR, Guild::LEN, Guild::VERSION),
+    (GuildMember::DISCRIMINATOR, GuildMember::LEN, GuildMember::VERSION),
+    (GuildWindow::DISCRIMINATOR, GuildWindow::LEN, GuildWindow::VERSION),
+    (GuildLeaderboard::DISCRIMINATOR, GuildLeaderboard::LEN, GuildLeaderboard::VERSION),
+    (EpochState::DISCRIMINATOR, EpochState::LEN, EpochState::VERSION),
+    (Epoch::DISCRIMINATOR, Epoch::LEN, Epoch::VERSION),
+    (SearchConfig::DISCRIMINATOR, SearchConfig::LEN, SearchConfig::VERSION),
+    (SearchState::DISCRIMINATOR, SearchState::LEN, SearchState::VERSION),
+    (GameStats::DISCRIMINATOR, GameStats::LEN, GameStats::VERSION),
+    (WinHistory::DISCRIMINATOR, WinHistory::LEN, WinHistory::VERSION),
+    (SeasonState::DISCRIMINATOR, SeasonState::LEN, SeasonState::VERSION),
+    (Season::DISCRIMINATOR, Season::LEN, Season::VERSION),
+];
+
+// Allocated sizes of superseded layouts and the version they were at, only these can be migrated.
+// Version 0 is everything deployed before the version header.
+const OLDER_LAYOUTS: [([u8; 8], usize, u8); 11] = [
+    (User::DISCRIMINATOR, 48, 0),
+    // Version 1 User, before the profile counters were appended
+    (User::DISCRIMINATOR, 8 + 1 + 8 * 5 + 16 + 8 + 32 + 8 + 8, 1),
+    // Version 2 User, before the achievement bitmasks were appended
+    (User::DISCRIMINATOR, 8 + 1 + 8 * 5 + 16 + 8 + 32 + 8 + 8 + 8 * 3 + 2 + 8 * 6, 2),
+    // Version 3 User, before the season points were appended
+    (User::DISCRIMINATOR, 8 + 1 + 8 * 5 + 16 + 8 + 32 + 8 + 8 + 8 * 3 + 2 + 8 * 6 + 4 * 2, 3),
+    (Jackpot::DISCRIMINATOR, 48, 0),
+    (StakingTreasury::DISCRIMINATOR, 8, 0),
+    (TotalWonPoints::DISCRIMINATOR, 16, 0),
+    (Treasury::DISCRIMINATOR, 16, 0),
+    (PlayerList::DISCRIMINATOR, 680, 0),
+    // The Borsh Vec<Pubkey> layout PlayerList had at version 1, before the ring buffer
+    (PlayerList::DISCRIMINATOR, 8 + 1 + 4 + 32 * 10, 1),
+    (Leaderboard::DISCRIMINATOR, 4008, 0),
+];
+
 #[program]
 #[allow(dead_code)]
 pub mod prime_slot_checker {
     use super::*;
 
     pub fn initialize(ctx: Context<Initialize>, _bump: u8) -> Result<()> {
+        ctx.accounts.jackpot.version = Jackpot::VERSION;
+        ctx.accounts.treasury.version = Treasury::VERSION;
         let jackpot = &mut ctx.accounts.jackpot;
         let treasury = &mut ctx.accounts.treasury;
-        let player_list = &mut ctx.accounts.player_list;
 
         // Initialize only if they have not been initialized already
         if jackpot.amount == 0 && jackpot.winner == Pubkey::default() {
@@ -21,30 +160,91 @@ pub mod prime_slot_checker {
             msg!("Jackpot pool initialized with 0 amount.");
         }
 
-        if player_list.players.is_empty() {
-            player_list.players = Vec::new();
+        // init_if_needed hands back the existing list on later calls, only a fresh one has no discriminator yet
+        let fresh = ctx.accounts.player_list.to_account_info().try_borrow_data()?[..8] == [0; 8];
+        let mut player_list = if fresh {
+            ctx.accounts.player_list.load_init()?
+        } else {
+            ctx.accounts.player_list.load_mut()?
+        };
+        if fresh {
+            player_list.version = PlayerList::VERSION;
+            player_list.window = DEFAULT_PLAYER_WINDOW;
             msg!("Player List initialized.");
         }
 
+        // The upgrade authority becomes the admin allowed to resize the window, this also claims a migrated list
+        if player_list.admin == Pubkey::default() {
+            player_list.admin = ctx.accounts.payer.key();
+        }
+
         // Only log treasury initialization, do not reinitialize or reset lamports
         msg!("Treasury account initialized {}", treasury.key());
 
         Ok(())
     }
 
+    pub fn set_player_window(ctx: Context<SetPlayerWindow>, window: u16) -> Result<()> {
+        if window == 0 || window as usize > PLAYER_LIST_CAPACITY {
+            return Err(ErrorCode::InvalidPlayerWindow.into());
+        }
+
+        let mut player_list = ctx.accounts.player_list.load_mut()?;
+        player_list.set_window(window);
+        let window_sum = player_list.window_sum;
+        msg!("Player list {} now sums the last {} players: {}", ctx.accounts.player_list.key(), window, window_sum);
+        Ok(())
+    }
+
+    pub fn initialize_game_stats(ctx: Context<InitializeGameStats>) -> Result<()> {
+        ctx.accounts.game_stats.version = GameStats::VERSION;
+        msg!("GameStats account initialized {}", ctx.accounts.game_stats.key());
+        Ok(())
+    }
+
+    // View: the protocol-wide counters plus live pool balances, read back from return data
+    pub fn get_stats(ctx: Context<GetStats>) -> Result<GameStatsSnapshot> {
+        let game_stats = &ctx.accounts.game_stats;
+        Ok(GameStatsSnapshot {
+            slot: Clock::get()?.slot,
+            total_rounds: game_stats.total_rounds,
+            total_wins: game_stats.total_wins,
+            total_superprimes: game_stats.total_superprimes,
+            total_deposited: game_stats.total_deposited,
+            total_paid_out: game_stats.total_paid_out,
+            total_yield_claimed: game_stats.total_yield_claimed,
+            treasury_balance: ctx.accounts.treasury.to_account_info().lamports(),
+            staking_treasury_balance: ctx.accounts.staking_treasury.to_account_info().lamports(),
+        })
+    }
+
+    pub fn initialize_win_history(ctx: Context<InitializeWinHistory>) -> Result<()> {
+        // init_if_needed hands back the existing history on later calls, only a fresh one has no discriminator yet
+        let fresh = ctx.accounts.win_history.to_account_info().try_borrow_data()?[..8] == [0; 8];
+        if fresh {
+            let mut win_history = ctx.accounts.win_history.load_init()?;
+            win_history.version = WinHistory::VERSION;
+            msg!("WinHistory account initialized {}", ctx.accounts.win_history.key());
+        }
+        Ok(())
+    }
+
     pub fn initialize_total_won_points(ctx: Context<InitializeTotalWonPoints>) -> Result<()> {
+        ctx.accounts.total_won_points.version = TotalWonPoints::VERSION;
         let total_won_points = &ctx.accounts.total_won_points;
         msg!("TotalWonPoints account initialized {}", total_won_points.key());
         Ok(())
     }
 
     pub fn initialize_staking_treasury(ctx: Context<InitializeStakingTreasury>) -> Result<()> {
+        ctx.accounts.staking_treasury.version = StakingTreasury::VERSION;
         let staking_treasury = &ctx.accounts.staking_treasury;
         msg!("Staking Treasury account initialized {}", staking_treasury.key());
         Ok(())
     }
 
     pub fn initialize_leaderboard(ctx: Context<InitializeLeaderboard>, _bump: u8) -> Result<()> {
+        ctx.accounts.leaderboard.version = Leaderboard::VERSION;
         let leaderboard = &mut ctx.accounts.leaderboard;
 
         // Initialize only if it has not been initialized already
@@ -59,6 +259,7 @@ pub mod prime_slot_checker {
     }
 
     pub fn initialize_user(ctx: Context<InitializeUser>, _bump: u8) -> Result<()> {
+        ctx.accounts.user.version = User::VERSION;
         let user = &mut ctx.accounts.user;
 
         // Initialize user points only if they have not been initialized already
@@ -80,9 +281,18 @@ pub mod prime_slot_checker {
         Ok(())
     }
 
-    pub fn initialize_rate(ctx: Context<InitializeRate>, _bump: u8) -> Result<()> {
-        let rate = &mut ctx.accounts.rate;
-        msg!("Rate account initialized {}", rate.key());
+    pub fn initialize_difficulty(ctx: Context<InitializeDifficulty>, target_win_interval: u64, retarget_interval: u64) -> Result<()> {
+        ctx.accounts.difficulty.version = Difficulty::VERSION;
+        let difficulty = &mut ctx.accounts.difficulty;
+
+        // Initialize only if it has not been initialized already
+        if difficulty.value == 0 {
+            open_difficulty(difficulty, target_win_interval, retarget_interval, Clock::get()?.slot)?;
+            msg!("Difficulty initialized: one win every {} slots, retarget every {} wins.", target_win_interval, retarget_interval);
+        } else {
+            msg!("Difficulty already initialized at {}", difficulty.value);
+        }
+
         Ok(())
     }
 
@@ -90,12 +300,20 @@ pub mod prime_slot_checker {
         let user = &mut ctx.accounts.user;
         let jackpot = &mut ctx.accounts.jackpot;
         let treasury = &mut ctx.accounts.treasury;
+        let vesting = &mut ctx.accounts.vesting;
+        let vesting_config = &ctx.accounts.vesting_config;
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        let game_stats = &mut ctx.accounts.game_stats;
+        let mut win_history = ctx.accounts.win_history.load_mut()?;
+        let season = &mut ctx.accounts.season;
         let payer = &ctx.accounts.payer;
-        let player_list = &mut ctx.accounts.player_list;
+        let mut player_list = ctx.accounts.player_list.load_mut()?;
         // let leaderboard = &mut ctx.accounts.leaderboard;
         let total_won_points = &mut ctx.accounts.total_won_points;
-        let rate = &mut ctx.accounts.rate;
-        let staking_treasury = &ctx.accounts.staking_treasury;
+        let difficulty = &mut ctx.accounts.difficulty;
+        let epoch = &mut ctx.accounts.epoch;
+        let yield_accumulator = &ctx.accounts.yield_accumulator;
+        open_vesting(vesting, payer.key());
 
         // Prevent transaction if user points are 10 or less
         if user.points <= 10 {
@@ -103,31 +321,17 @@ pub mod prime_slot_checker {
         }
 
         // Convert user public key to a number in the range of 1 to 100,000
-        let payer_pubkey = payer.key();
         let user_pubkey = user.key();
         let user_number = pubkey_to_number(&user_pubkey);
 
         // Get the current slot
         let slot = Clock::get()?.slot;
-        let last_won_slot = user.last_won_slot;
 
         // Calculate the power-up percentage based on the slot difference
-        let slots_since_last_win = slot - last_won_slot;
-        let power_up = if last_won_slot == 0 {
-            0.1 // New user
-        } else if slots_since_last_win >= 600 {
-            0.75
-        } else if slots_since_last_win >= 300 {
-            0.5
-        } else if slots_since_last_win >= 100 {
-            0.25
-        } else {
-            0.1
-        };
+        let power_up = power_up_for(user.last_won_slot, slot);
 
-        // Convert last 10 players' pubkeys to numbers and add to slot
-        let recent_players: Vec<Pubkey> = player_list.players.iter().rev().take(10).cloned().collect();
-        let recent_players_sum: u64 = recent_players.iter().map(|pk| pubkey_to_number(pk) as u64).sum();
+        // Sum of the recent players' numbers inside the list's window
+        let recent_players_sum = recent_players_sum(&player_list);
 
         // Get current UNIX time and convert to number
         let unix_time = Clock::get()?.unix_timestamp;
@@ -136,59 +340,27 @@ pub mod prime_slot_checker {
         // Calculate the number to test
         let number_to_test = slot + user_number as u64 + recent_players_sum + time_number;
 
-        // deduct from user and total won points when user starts spending won points
-        if user.points <= user.won_points {
-            user.won_points -= 10;
-            if total_won_points.points >= 10 {
-                total_won_points.points -= 10 as u64;
-            } else {
-                total_won_points.points = 0;
-            }
-        }
-
-        // insert points in miner
-        user.points -= 10;
-        jackpot.amount += 10;
+        accrue_yield(user, yield_accumulator);
+        spend_round_points(user, total_won_points, jackpot, ROUND_COST)?;
+        record_epoch_rounds(epoch, slot, 1);
 
-        // Check if the resulting number is prime
-        if is_prime(number_to_test, 5) {
-            let reward_points = (jackpot.amount as f64 * power_up).round() as i64;
-
-            user.points += reward_points;
-            user.won_points += reward_points;
-            total_won_points.points += reward_points as u64;
-            user.last_won_slot = slot;
-            jackpot.winner = payer.key(); // Assign the payer's pubkey as the winner
-            msg!("Slot {} + User number {} + Players sum {} + Time number {} = {} is prime. Payer {} rewarded with {} points.", slot, user_number, recent_players_sum, time_number, number_to_test, payer.key(), reward_points);
-
-            transfer_from_treasury(treasury, payer, number_to_test, power_up)?;
-
-            msg!("User won with {} power-up", power_up);
-
-            // Send event
-            msg!("PrimeFound: slot={}, user_pubkey={}, power_up={}, number_to_test={}, reward_points={}",
-                slot,
-                payer_pubkey,
-                power_up,
-                number_to_test,
-                reward_points
-            );
-
-            // Calculate the new point rate after winning
-            calculate_point_rate_internal(staking_treasury, total_won_points, rate)?;
-
-            // Adjust jackpot amount to ensure it doesn't go below zero
-            if jackpot.amount >= reward_points {
-                jackpot.amount -= reward_points;
-            } else {
-                jackpot.amount = 0;
-            }
+        // Check if the resulting number passes the difficulty filter and is prime
+        let won = meets_difficulty(number_to_test, difficulty.value) && is_prime(number_to_test, 5);
+        if won {
+            msg!("Slot {} + User number {} + Players sum {} + Time number {} = {} is prime at difficulty {}.", slot, user_number, recent_players_sum, time_number, number_to_test, difficulty.value);
+            award_prime(user, jackpot, treasury, vesting, vesting_config, fee_vault, game_stats, Some(&mut win_history), Some(&mut *season), payer, total_won_points, slot, number_to_test, power_up, has_suffix(number_to_test, SUPERPRIME_SUFFIX, SUPERPRIME_DIGITS), PayoutCap::FULL)?;
+            record_win(difficulty, slot);
+            record_epoch_win(epoch, payer.key(), slot);
         } else {
             msg!("Slot {} + User number {} + Players sum {} + Time number {} = {} is not prime. Jackpot pool increased by 10 points.", slot, user_number, recent_players_sum, time_number, number_to_test);
         }
 
+        sync_reward_debt(user, yield_accumulator);
+        record_play(user, slot, 1, won);
+        record_game_rounds(game_stats, 1);
+
         // Update the player list with the latest user
-        update_player_list(player_list, payer.key());
+        player_list.push(payer.key(), slot);
 
         // Log the last 10 user public keys
         // update_leaderboard(leaderboard, payer.key(), user.won_points);
@@ -205,410 +377,4576 @@ pub mod prime_slot_checker {
         Ok(())
     }
 
-    pub fn trade_won_points(ctx: Context<TradeWonPoints>, _bump: u8) -> Result<()> {
+    pub fn check_slots(ctx: Context<CheckSlot>, n: u8) -> Result<()> {
         let user = &mut ctx.accounts.user;
-        let rate = &ctx.accounts.rate;
-        let staking_treasury = &mut ctx.accounts.staking_treasury;
+        let jackpot = &mut ctx.accounts.jackpot;
+        let treasury = &mut ctx.accounts.treasury;
+        let vesting = &mut ctx.accounts.vesting;
+        let vesting_config = &ctx.accounts.vesting_config;
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        let game_stats = &mut ctx.accounts.game_stats;
+        let mut win_history = ctx.accounts.win_history.load_mut()?;
+        let season = &mut ctx.accounts.season;
         let payer = &ctx.accounts.payer;
+        let mut player_list = ctx.accounts.player_list.load_mut()?;
         let total_won_points = &mut ctx.accounts.total_won_points;
+        let difficulty = &mut ctx.accounts.difficulty;
+        let epoch = &mut ctx.accounts.epoch;
+        let yield_accumulator = &ctx.accounts.yield_accumulator;
+        open_vesting(vesting, payer.key());
+
+        if n == 0 {
+            return Err(ErrorCode::InvalidBatchSize.into());
+        }
 
-        // Check if user has at least 1000 won points
-        if user.won_points < 1000 {
+        // Prevent transaction if user can't cover every round plus the usual 10 point reserve
+        let rounds = n as i64;
+        if user.points <= ROUND_COST * rounds {
             return Err(ProgramError::InsufficientFunds.into());
         }
 
-        // Calculate the lamports to transfer
-        let lamports_to_transfer = (1000.0 * rate.value) as u64;
+        let user_number = pubkey_to_number(&user.key());
+        let slot = Clock::get()?.slot;
+        let power_up = power_up_for(user.last_won_slot, slot);
+        let recent_players_sum = recent_players_sum(&player_list);
+        let unix_time = Clock::get()?.unix_timestamp;
+        let time_number = (unix_time % 100_000) as u64;
+        let base_number = slot + user_number as u64 + recent_players_sum + time_number;
 
-        // Check if staking treasury has enough lamports
-        let staking_treasury_balance = **staking_treasury.to_account_info().lamports.borrow();
-        if staking_treasury_balance < lamports_to_transfer {
-            return Err(ProgramError::InsufficientFunds.into());
+        // Refuse batches whose primality tests can't fit in a single transaction
+        let largest_candidate = candidate_for_nonce(base_number, n - 1);
+        let max_rounds = max_prime_tests_within_budget(CHECK_SLOTS_CU_BUDGET, largest_candidate, 5);
+        if n as u64 > max_rounds {
+            msg!("Batch of {} exceeds compute budget, at most {} candidates fit.", n, max_rounds);
+            return Err(ErrorCode::BatchExceedsComputeBudget.into());
+        }
+
+        accrue_yield(user, yield_accumulator);
+        for _ in 0..n {
+            spend_round_points(user, total_won_points, jackpot, ROUND_COST)?;
+        }
+        record_epoch_rounds(epoch, slot, n as u64);
+
+        // Test candidates in nonce order, settling at most one win
+        let mut winning_nonce = None;
+        for nonce in 0..n {
+            let number_to_test = candidate_for_nonce(base_number, nonce);
+            if meets_difficulty(number_to_test, difficulty.value) && is_prime(number_to_test, 5) {
+                winning_nonce = Some(nonce);
+                msg!("Base {} + nonce {} = {} is prime at difficulty {}.", base_number, nonce, number_to_test, difficulty.value);
+                award_prime(user, jackpot, treasury, vesting, vesting_config, fee_vault, game_stats, Some(&mut win_history), Some(&mut *season), payer, total_won_points, slot, number_to_test, power_up, has_suffix(number_to_test, SUPERPRIME_SUFFIX, SUPERPRIME_DIGITS), PayoutCap::FULL)?;
+                record_win(difficulty, slot);
+                record_epoch_win(epoch, payer.key(), slot);
+                break;
+            }
         }
 
-        // Transfer lamports from staking treasury to payer
-        **staking_treasury.to_account_info().lamports.borrow_mut() -= lamports_to_transfer;
-        **payer.to_account_info().lamports.borrow_mut() += lamports_to_transfer;
+        if winning_nonce.is_none() {
+            msg!("None of {} candidates from base {} are prime. Jackpot pool increased by {} points.", n, base_number, ROUND_COST * rounds);
+        }
 
-        // Deduct 1000 won points from user
-        user.points -= 1000;
-        user.won_points -= 1000;
-        total_won_points.points -= 1000;
+        sync_reward_debt(user, yield_accumulator);
+        record_play(user, slot, n as u64, winning_nonce.is_some());
+        record_game_rounds(game_stats, n as u64);
+        player_list.push(payer.key(), slot);
 
-        msg!("Traded 1000 won points for {} lamports", lamports_to_transfer);
-        msg!("User {} now has {} won points", payer.key(), user.won_points);
+        msg!("User {} now has {} points.", payer.key(), user.points);
+        msg!("Jackpot pool now has {} points.", jackpot.amount);
+        msg!("User {} has {} won points.", payer.key(), user.won_points);
 
         Ok(())
     }
 
+    pub fn initialize_search_config(ctx: Context<InitializeSearchConfig>, suffix_digits: u8, suffix: u64, target_proof_interval: u64, retarget_interval: u64) -> Result<()> {
+        ctx.accounts.search_config.version = SearchConfig::VERSION;
+        ctx.accounts.search_difficulty.version = Difficulty::VERSION;
+        let search_config = &mut ctx.accounts.search_config;
 
-    pub fn pay_for_points(ctx: Context<PayForPoints>, _bump: u8) -> Result<()> {
-        let user = &mut ctx.accounts.user;
-        let treasury = &mut ctx.accounts.treasury;
-        let staking_treasury = &mut ctx.accounts.staking_treasury;
-        let payer = &ctx.accounts.payer;
+        // The first initializer becomes the admin allowed to retune the difficulty
+        if search_config.admin == Pubkey::default() {
+            validate_search_suffix(suffix_digits, suffix)?;
+            search_config.admin = ctx.accounts.payer.key();
+            search_config.suffix_digits = suffix_digits;
+            search_config.suffix = suffix;
+            search_config.payout_bps = DEFAULT_PROOF_PAYOUT_BPS;
+            search_config.treasury_floor = 0;
+            // Proofs are mined off-chain, so they retarget on their own and never move the round difficulty
+            open_difficulty(&mut ctx.accounts.search_difficulty, target_proof_interval, retarget_interval, Clock::get()?.slot)?;
+            msg!("Search config initialized: primes must end in {} ({} digits), one proof every {} slots.", suffix, suffix_digits, target_proof_interval);
+        } else {
+            msg!("Search config already initialized {}", search_config.key());
+        }
 
-        // Transfer 0.8 SOL to the treasury using the system program
-        let transfer_instruction = anchor_lang::solana_program::system_instruction::transfer(
-            &payer.key(),
-            &treasury.key(),
-            800_000_000,
-        );
-        anchor_lang::solana_program::program::invoke(
-            &transfer_instruction,
-            &[
-                payer.to_account_info(),
-                treasury.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ],
-        )?;
+        Ok(())
+    }
 
-        // Transfer 0.2 SOL to the staking treasury
-        let staking_transfer_instruction = anchor_lang::solana_program::system_instruction::transfer(
-            &payer.key(),
-            &staking_treasury.key(),
-            200_000_000,
-        );
-        anchor_lang::solana_program::program::invoke(
-            &staking_transfer_instruction,
-            &[
-                payer.to_account_info(),
-                staking_treasury.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ],
-        )?;
+    pub fn set_search_difficulty(ctx: Context<SetSearchDifficulty>, suffix_digits: u8, suffix: u64) -> Result<()> {
+        let search_config = &mut ctx.accounts.search_config;
 
-        // Add points to user
-        user.points += 1000;
-        msg!("User {} paid 1 SOL and received 1000 points.", payer.key());
-        msg!("Buy Points: Yield Pool {}",  **staking_treasury.to_account_info().lamports.borrow());
+        validate_search_suffix(suffix_digits, suffix)?;
+        search_config.suffix_digits = suffix_digits;
+        search_config.suffix = suffix;
+        msg!("Search difficulty set: primes must end in {} ({} digits).", suffix, suffix_digits);
 
         Ok(())
     }
 
-    pub fn claim_lamports(ctx: Context<ClaimLamports>, _bump: u8) -> Result<()> {
+    pub fn set_proof_payout(ctx: Context<SetProofPayout>, payout_bps: u16, treasury_floor: u64) -> Result<()> {
+        let search_config = &mut ctx.accounts.search_config;
+
+        if payout_bps > 10_000 {
+            return Err(ErrorCode::InvalidProofPayout.into());
+        }
+        search_config.payout_bps = payout_bps;
+        search_config.treasury_floor = treasury_floor;
+        msg!("Proofs now earn {} bps of a win's payout and never take the treasury below {} lamports.", payout_bps, treasury_floor);
+
+        Ok(())
+    }
+
+    pub fn submit_prime_proof(ctx: Context<SubmitPrimeProof>, slot: u64, nonce: u64) -> Result<()> {
+        ctx.accounts.search_state.version = SearchState::VERSION;
         let user = &mut ctx.accounts.user;
-        let staking_treasury = &mut ctx.accounts.staking_treasury;
+        let jackpot = &mut ctx.accounts.jackpot;
+        let treasury = &mut ctx.accounts.treasury;
+        let vesting = &mut ctx.accounts.vesting;
+        let vesting_config = &ctx.accounts.vesting_config;
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        let game_stats = &mut ctx.accounts.game_stats;
+        let mut win_history = ctx.accounts.win_history.load_mut()?;
+        let season = &mut ctx.accounts.season;
         let payer = &ctx.accounts.payer;
+        let mut player_list = ctx.accounts.player_list.load_mut()?;
         let total_won_points = &mut ctx.accounts.total_won_points;
+        let search_config = &ctx.accounts.search_config;
+        let search_state = &mut ctx.accounts.search_state;
+        let search_difficulty = &mut ctx.accounts.search_difficulty;
+        let epoch = &mut ctx.accounts.epoch;
+        let yield_accumulator = &ctx.accounts.yield_accumulator;
+        open_vesting(vesting, payer.key());
+
+        // Prevent transaction if user points are 10 or less
+        if user.points <= 10 {
+            return Err(ProgramError::InsufficientFunds.into());
+        }
 
-        // Calculate the ratio of user's won points to total won points
-        let user_won_points = user.won_points as u64;
-        let ratio = user_won_points as f64 / total_won_points.points as f64;
-        msg!("User ratio: {}", ratio);
-        msg!("User last claimed slot: {}", user.last_claimed_slot);
-        msg!("User last claimed lamports: {}", user.last_claimed_lamports);
+        // One proof per user per slot hash, and only against hashes still in the sysvar
+        if slot <= search_state.last_proof_slot {
+            return Err(ErrorCode::StaleProof.into());
+        }
+        let slot_hash = find_slot_hash(&ctx.accounts.slot_hashes, slot)?;
+
+        let number_to_test = search_candidate(&slot_hash, &payer.key().to_bytes(), nonce);
+        if !has_suffix(number_to_test, search_config.suffix, search_config.suffix_digits) {
+            return Err(ErrorCode::SuffixMismatch.into());
+        }
+        if !meets_difficulty(number_to_test, search_difficulty.value) {
+            return Err(ErrorCode::ProofBelowDifficulty.into());
+        }
+        if !is_prime(number_to_test, 5) {
+            return Err(ErrorCode::NotPrime.into());
+        }
 
-        // Get the current slot
         let current_slot = Clock::get()?.slot;
+        let power_up = power_up_for(user.last_won_slot, current_slot);
+
+        accrue_yield(user, yield_accumulator);
+        spend_round_points(user, total_won_points, jackpot, ROUND_COST)?;
+        record_epoch_rounds(epoch, current_slot, 1);
 
-        // Transfer lamports from staking treasury to the payer
-        let lamports_transferred = transfer_from_staking_treasury(staking_treasury, payer, ratio)?;
+        // A miner chooses which candidates to submit, so a proof can never be a SuperPrime and take the whole treasury.
+        // Off-chain hash power can win every slot, so proofs take a capped share and stop at the treasury floor.
+        msg!("Proof accepted: H(slot hash {} || {} || nonce {}) = {} is prime at difficulty {}.", slot, payer.key(), nonce, number_to_test, search_difficulty.value);
+        let cap = PayoutCap { bps: search_config.payout_bps, floor: search_config.treasury_floor };
+        award_prime(user, jackpot, treasury, vesting, vesting_config, fee_vault, game_stats, Some(&mut win_history), Some(&mut *season), payer, total_won_points, current_slot, number_to_test, power_up, false, cap)?;
+        record_win(search_difficulty, current_slot);
+        record_epoch_win(epoch, payer.key(), current_slot);
 
-        // Update user's last claimed slot and last claimed lamports
-        user.last_claimed_slot = current_slot;
-        user.last_claimed_lamports = lamports_transferred;
+        sync_reward_debt(user, yield_accumulator);
+        record_play(user, current_slot, 1, true);
+        record_game_rounds(game_stats, 1);
+        search_state.last_proof_slot = slot;
+        search_state.proofs += 1;
 
-        // Deduct user won points from total
-        total_won_points.points -= user_won_points;
+        player_list.push(payer.key(), current_slot);
 
-        // All points swapped for lamports
-        user.won_points = 0;
+        msg!("User {} now has {} points.", payer.key(), user.points);
+        msg!("User {} has submitted {} proofs.", payer.key(), search_state.proofs);
+
+        Ok(())
+    }
 
-        // Deduct user won points from user points
-        if user_won_points <= user.points as u64 {
-            user.points -= user_won_points as i64;
+    pub fn initialize_epochs(ctx: Context<InitializeEpochs>, epoch_length: u64, draw_bps: u16) -> Result<()> {
+        ctx.accounts.epoch_state.version = EpochState::VERSION;
+        ctx.accounts.epoch.version = Epoch::VERSION;
+        let epoch_state = &mut ctx.accounts.epoch_state;
+        let epoch = &mut ctx.accounts.epoch;
+
+        if epoch_length == 0 || draw_bps > MAX_EPOCH_DRAW_BPS {
+            return Err(ErrorCode::InvalidEpochParams.into());
         }
 
-        msg!("Updated user last claimed slot: {}", user.last_claimed_slot);
-        msg!("Updated user last claimed lamports: {} with {} points", user.last_claimed_lamports, user_won_points);
+        let slot = Clock::get()?.slot;
+        epoch_state.current_epoch = 0;
+        epoch_state.epoch_length = epoch_length;
+        epoch_state.draw_bps = draw_bps;
+        open_epoch(epoch, 0, slot, epoch_length, 0);
 
+        msg!("Epoch 0 opened from slot {} to {}, drawing {} bps of the treasury.", epoch.start_slot, epoch.end_slot, draw_bps);
         Ok(())
     }
-}
 
-fn calculate_point_rate_internal(
-    staking_treasury: &Account<StakingTreasury>,
-    total_won_points: &Account<TotalWonPoints>,
-    rate: &mut Account<Rate>,
-) -> Result<()> {
-    let staking_treasury_balance = **staking_treasury.to_account_info().lamports.borrow() as f64;
-    let total_points = total_won_points.points as f64;
+    pub fn close_epoch<'info>(ctx: Context<'_, '_, 'info, 'info, CloseEpoch<'info>>) -> Result<()> {
+        ctx.accounts.next_epoch.version = Epoch::VERSION;
+        let epoch_state = &mut ctx.accounts.epoch_state;
+        let epoch = &mut ctx.accounts.epoch;
+        let next_epoch = &mut ctx.accounts.next_epoch;
+        let treasury = &mut ctx.accounts.treasury;
 
-    if total_points == 0.0 {
-        rate.value = 0.0;
-    } else {
-        rate.value = staking_treasury_balance / total_points;
-    }
+        let slot = Clock::get()?.slot;
+        if slot < epoch.end_slot {
+            return Err(ErrorCode::EpochStillOpen.into());
+        }
 
-    msg!("Point rate: {} lamports per point", rate.value);
-    Ok(())
-}
+        // Move this epoch's slice of the treasury into the pot, on top of whatever rolled forward
+        let treasury_rent = Rent::get()?.minimum_balance(treasury.to_account_info().data_len());
+        let treasury_available = treasury.to_account_info().lamports().saturating_sub(treasury_rent);
+        let draw = (treasury_available as u128 * epoch_state.draw_bps as u128 / 10_000) as u64;
+        **treasury.to_account_info().try_borrow_mut_lamports()? -= draw;
+        **epoch_state.to_account_info().try_borrow_mut_lamports()? += draw;
 
-fn transfer_from_staking_treasury(
-    staking_treasury: &mut Account<StakingTreasury>,
-    payer: &Signer,
-    ratio: f64,
-) -> Result<u64> {
-    let staking_treasury_balance = **staking_treasury.to_account_info().lamports.borrow();
-    let rent_exemption = Rent::get()?.minimum_balance(staking_treasury.to_account_info().data_len());
+        let pot = epoch.carried_in + draw;
+        epoch.pot = pot;
+
+        // Split the pot across winners by primes found; crank passes winner wallets in the recorded order
+        let total_primes: u64 = epoch.winners.iter().map(|w| w.primes).sum();
+        if ctx.remaining_accounts.len() != epoch.winners.len() {
+            return Err(ErrorCode::WinnerAccountMismatch.into());
+        }
+        let epoch_id = epoch.epoch_id;
+        let mut paid = 0u64;
+        for (winner, wallet) in epoch.winners.iter_mut().zip(ctx.remaining_accounts.iter()) {
+            if wallet.key() != winner.winner {
+                return Err(ErrorCode::WinnerAccountMismatch.into());
+            }
+            let payout = (pot as u128 * winner.primes as u128 / total_primes as u128) as u64;
+            **epoch_state.to_account_info().try_borrow_mut_lamports()? -= payout;
+            **wallet.try_borrow_mut_lamports()? += payout;
+            winner.payout = payout;
+            paid += payout;
+            msg!("Epoch {} winner {} found {} primes and was paid {} lamports.", epoch_id, winner.winner, winner.primes, payout);
+        }
 
-    // Calculate the amount to transfer based on the ratio
-    let transfer_amount = (staking_treasury_balance.checked_sub(rent_exemption).ok_or(ProgramError::InsufficientFunds)? as f64 * ratio) as u64;
+        // Whatever wasn't paid out (no winners, rounding dust) seeds the next epoch
+        let rolled_forward = pot - paid;
+        record_game_payout(&mut ctx.accounts.game_stats, paid);
+        epoch.rolled_forward = rolled_forward;
+        epoch.settled = true;
 
-    // Ensure the calculated transfer amount doesn't exceed the balance minus rent exemption
-    if transfer_amount > staking_treasury_balance.checked_sub(rent_exemption).ok_or(ProgramError::InsufficientFunds)? {
-        return Err(ProgramError::InsufficientFunds.into());
+        epoch_state.current_epoch += 1;
+        open_epoch(next_epoch, epoch_state.current_epoch, epoch.end_slot.max(slot), epoch_state.epoch_length, rolled_forward);
+
+        msg!("Epoch {} closed: {} rounds, pot {} lamports, paid {}, rolled forward {}.", epoch.epoch_id, epoch.rounds, pot, paid, rolled_forward);
+        emit!(EpochClosed {
+            epoch_id: epoch.epoch_id,
+            rounds: epoch.rounds,
+            winners: epoch.winners.len() as u8,
+            pot,
+            paid,
+            rolled_forward,
+        });
+
+        Ok(())
     }
 
-    **staking_treasury.to_account_info().lamports.borrow_mut() -= transfer_amount;
-    **payer.to_account_info().lamports.borrow_mut() += transfer_amount;
+    pub fn initialize_seasons(ctx: Context<InitializeSeasons>, season_length: u64, pool_bps: u16, distribution: Vec<u16>) -> Result<()> {
+        ctx.accounts.season_state.version = SeasonState::VERSION;
+        ctx.accounts.season.version = Season::VERSION;
+        let season_state = &mut ctx.accounts.season_state;
+        let season = &mut ctx.accounts.season;
 
-    msg!("Transferred {} lamports from staking treasury {} to user {}", transfer_amount, staking_treasury.key(), payer.key());
-    Ok(transfer_amount)
-}
+        if season_length == 0 {
+            return Err(ErrorCode::InvalidSeasonParams.into());
+        }
+        validate_season_config(pool_bps, &distribution)?;
 
-fn transfer_from_treasury(treasury: &mut Account<Treasury>, payer: &Signer, number_to_test: u64, power_up: f64) -> Result<()> {
-    let treasury_balance = **treasury.to_account_info().lamports.borrow();
-    let rent_exemption = Rent::get()?.minimum_balance(treasury.to_account_info().data_len());
-    let payer_pubkey = payer.key();
+        let slot = Clock::get()?.slot;
+        season_state.admin = ctx.accounts.payer.key();
+        season_state.current_season = 0;
+        season_state.season_length = season_length;
+        season_state.pool_bps = pool_bps;
+        season_state.pool = 0;
+        season_state.distribution = distribution;
+        open_season(season, 0, slot, season_length, 0);
 
-    // Calculate the amount to transfer based on the prime number ending and power-up
-    let transfer_amount = if number_to_test % 100 == 1 {
-        treasury_balance.checked_sub(rent_exemption).ok_or(ProgramError::InsufficientFunds)?
-    } else {
-        (treasury_balance.checked_sub(rent_exemption).ok_or(ProgramError::InsufficientFunds)? as f64 * power_up) as u64
-    };
+        msg!("Season 0 opened from slot {} to {}, funded by {} bps of purchases.", season.start_slot, season.end_slot, pool_bps);
+        Ok(())
+    }
 
-    **treasury.to_account_info().lamports.borrow_mut() -= transfer_amount;
-    **payer.to_account_info().lamports.borrow_mut() += transfer_amount;
+    pub fn set_season_config(ctx: Context<SetSeasonConfig>, pool_bps: u16, distribution: Vec<u16>) -> Result<()> {
+        let season_state = &mut ctx.accounts.season_state;
 
-    msg!("Transferred {} lamports from treasury {} to user {}", transfer_amount, treasury.key(), payer.key());
-    msg!("Winner: User: {} Lamports: {} Power-up: {}", payer_pubkey, transfer_amount, power_up);
-    Ok(())
-}
+        validate_season_config(pool_bps, &distribution)?;
+        season_state.pool_bps = pool_bps;
+        season_state.distribution = distribution;
 
-fn update_player_list(player_list: &mut Account<PlayerList>, new_player: Pubkey) {
-    if player_list.players.len() >= 10 {
-        player_list.players.remove(0);
+        msg!("Season pool set to {} bps of purchases, paying {} ranks.", pool_bps, season_state.distribution.len());
+        Ok(())
     }
-    player_list.players.push(new_player);
-}
-/*
-fn update_leaderboard(leaderboard: &mut Account<Leaderboard>, user: Pubkey, points: i64) {
-    // Remove the user if they are already in the leaderboard
-    leaderboard.users.retain(|entry| entry.user != user);
 
-    // Add the user back with updated points
-    leaderboard.users.push(UserEntry { user, points });
+    pub fn finalize_season<'info>(ctx: Context<'_, '_, 'info, 'info, FinalizeSeason<'info>>) -> Result<()> {
+        ctx.accounts.next_season.version = Season::VERSION;
+        let season_state = &mut ctx.accounts.season_state;
+        let season = &mut ctx.accounts.season;
+        let next_season = &mut ctx.accounts.next_season;
 
-    // Sort the leaderboard by points in descending order and keep only the top 100
-    leaderboard.users.sort_by(|a, b| b.points.cmp(&a.points));
-    leaderboard.users.truncate(100);
+        let slot = Clock::get()?.slot;
+        if slot < season.end_slot {
+            return Err(ErrorCode::SeasonStillOpen.into());
+        }
 
-    // Log the specific user and their points
-    msg!("Leaderboard: User: {}, Points: {}", user.to_string(), points);
-}
-*/
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(init_if_needed, payer = payer, space = Jackpot::LEN, seeds = [b"jackpot"], bump)]
-    pub jackpot: Box<Account<'info, Jackpot>>,
-    #[account(init_if_needed, payer = payer, space = Treasury::LEN, seeds = [b"treasury"], bump)]
-    pub treasury: Box<Account<'info, Treasury>>,
-    #[account(init_if_needed, payer = payer, space = PlayerList::LEN, seeds = [b"player_list"], bump)]
-    pub player_list: Box<Account<'info, PlayerList>>,
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        // The pool collected this season on top of whatever rolled forward, both already sit on season_state
+        let pot = season.carried_in + season_state.pool;
+        season_state.pool = 0;
+        season.pot = pot;
 
-#[derive(Accounts)]
-pub struct InitializeTotalWonPoints<'info> {
-    #[account(init_if_needed, payer = payer, space = TotalWonPoints::LEN, seeds = [b"total_won_points"], bump)]
-    pub total_won_points: Box<Account<'info, TotalWonPoints>>,
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        // Pay the final standings by rank; crank passes player wallets in standings order
+        if ctx.remaining_accounts.len() != season.standings.len() {
+            return Err(ErrorCode::WinnerAccountMismatch.into());
+        }
+        let season_id = season.season_id;
+        let mut paid = 0u64;
+        for (rank, (entry, wallet)) in season.standings.iter_mut().zip(ctx.remaining_accounts.iter()).enumerate() {
+            if wallet.key() != entry.player {
+                return Err(ErrorCode::WinnerAccountMismatch.into());
+            }
+            let share_bps = season_state.distribution.get(rank).copied().unwrap_or(0);
+            let payout = (pot as u128 * share_bps as u128 / 10_000) as u64;
+            **season_state.to_account_info().try_borrow_mut_lamports()? -= payout;
+            **wallet.try_borrow_mut_lamports()? += payout;
+            entry.payout = payout;
+            paid += payout;
+            msg!("Season {} rank {} {} won {} points and was paid {} lamports.", season_id, rank + 1, entry.player, entry.won_points, payout);
+        }
 
-#[derive(Accounts)]
-pub struct InitializeStakingTreasury<'info> {
-    #[account(init_if_needed, payer = payer, space = StakingTreasury::LEN, seeds = [b"staking_treasury"], bump)]
-    pub staking_treasury: Box<Account<'info, StakingTreasury>>,
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        // Unpaid ranks and rounding dust seed the next season
+        let rolled_forward = pot - paid;
+        record_game_payout(&mut ctx.accounts.game_stats, paid);
+        season.paid = paid;
+        season.rolled_forward = rolled_forward;
+        season.finalized = true;
 
-#[derive(Accounts)]
-pub struct InitializeLeaderboard<'info> {
-    #[account(init_if_needed, payer = payer, space = Leaderboard::LEN, seeds = [b"leaderboard"], bump)]
-    pub leaderboard: Box<Account<'info, Leaderboard>>,
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        season_state.current_season += 1;
+        open_season(next_season, season_state.current_season, season.end_slot.max(slot), season_state.season_length, rolled_forward);
+        next_season.standings = season.late_standings.clone();
 
-#[derive(Accounts)]
-pub struct InitializeUser<'info> {
-    #[account(init_if_needed, payer = payer, space = User::LEN, seeds = [b"user", payer.key().as_ref()], bump)]
-    pub user: Box<Account<'info, User>>,
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        msg!("Season {} finalized: pot {} lamports, paid {}, rolled forward {}.", season_id, pot, paid, rolled_forward);
+        emit!(SeasonFinalized {
+            season_id,
+            ranked: season.standings.len() as u8,
+            pot,
+            paid,
+            rolled_forward,
+        });
 
-#[derive(Accounts)]
-pub struct InitializeRate<'info> {
-    #[account(init_if_needed, payer = payer, space = Rate::LEN, seeds = [b"rate"], bump)]
-    pub rate: Box<Account<'info, Rate>>,
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct CheckSlot<'info> {
-    #[account(mut, seeds = [b"user", payer.key().as_ref()], bump)]
-    pub user: Box<Account<'info, User>>,
-    #[account(mut, seeds = [b"jackpot"], bump)]
-    pub jackpot: Box<Account<'info, Jackpot>>,
-    #[account(mut, seeds = [b"total_won_points"], bump)]
-    pub total_won_points: Box<Account<'info, TotalWonPoints>>,
-    #[account(mut, seeds = [b"treasury"], bump)]
-    pub treasury: Box<Account<'info, Treasury>>,
-    #[account(mut, seeds = [b"player_list"], bump)]
-    pub player_list: Box<Account<'info, PlayerList>>,
-    #[account(mut, seeds = [b"leaderboard"], bump)]
-    pub leaderboard: Box<Account<'info, Leaderboard>>,
-    #[account(mut, seeds = [b"staking_treasury"], bump)]
-    pub staking_treasury: Box<Account<'info, StakingTreasury>>,
-    #[account(mut, seeds = [b"rate"], bump)]
-    pub rate: Box<Account<'info, Rate>>,
-    pub payer: Signer<'info>,
-}
+    pub fn create_table(ctx: Context<CreateTable>, table_id: u64, config: TableConfig) -> Result<()> {
+        ctx.accounts.table.version = Table::VERSION;
+        ctx.accounts.jackpot.version = Jackpot::VERSION;
+        ctx.accounts.treasury.version = Treasury::VERSION;
+        {
+            let mut player_list = ctx.accounts.player_list.load_init()?;
+            player_list.version = PlayerList::VERSION;
+            player_list.window = DEFAULT_PLAYER_WINDOW;
+            player_list.admin = ctx.accounts.authority.key();
+        }
+        let table = &mut ctx.accounts.table;
 
-#[derive(Accounts)]
-pub struct PayForPoints<'info> {
-    #[account(mut, seeds = [b"user", payer.key().as_ref()], bump)]
-    pub user: Box<Account<'info, User>>,
-    #[account(mut, seeds = [b"treasury"], bump)]
-    pub treasury: Box<Account<'info, Treasury>>,
-    #[account(mut, seeds = [b"staking_treasury"], bump)]
-    pub staking_treasury: Box<Account<'info, StakingTreasury>>,
-    pub payer: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        validate_table_config(&config)?;
+        table.table_id = table_id;
+        table.authority = ctx.accounts.authority.key();
+        table.config = config;
 
-#[derive(Accounts)]
-pub struct ClaimLamports<'info> {
-    #[account(mut, seeds = [b"user", payer.key().as_ref()], bump)]
-    pub user: Box<Account<'info, User>>,
+        msg!("Table {} created by {} with entry cost {} points, private: {}", table_id, table.authority, table.config.entry_cost, table.config.private);
+        Ok(())
+    }
+
+    pub fn add_table_player(ctx: Context<AddTablePlayer>, table_id: u64, player: Pubkey) -> Result<()> {
+        ctx.accounts.seat.version = TableSeat::VERSION;
+        let seat = &mut ctx.accounts.seat;
+        seat.table_id = table_id;
+        seat.player = player;
+        msg!("Player {} seated at table {}", player, table_id);
+        Ok(())
+    }
+
+    pub fn remove_table_player(_ctx: Context<RemoveTablePlayer>, table_id: u64, player: Pubkey) -> Result<()> {
+        msg!("Player {} removed from table {}", player, table_id);
+        Ok(())
+    }
+
+    pub fn fund_table(ctx: Context<FundTable>, table_id: u64, lamports: u64) -> Result<()> {
+        let transfer_instruction = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.payer.key(),
+            &ctx.accounts.treasury.key(),
+            lamports,
+        );
+        anchor_lang::solana_program::program::invoke(
+            &transfer_instruction,
+            &[
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.treasury.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
+        msg!("Table {} treasury funded with {} lamports by {}", table_id, lamports, ctx.accounts.payer.key());
+        Ok(())
+    }
+
+    pub fn check_table_slot(ctx: Context<CheckTableSlot>, table_id: u64) -> Result<()> {
+        let user = &mut ctx.accounts.user;
+        let table = &ctx.accounts.table;
+        let jackpot = &mut ctx.accounts.jackpot;
+        let treasury = &mut ctx.accounts.treasury;
+        let vesting = &mut ctx.accounts.vesting;
+        let vesting_config = &ctx.accounts.vesting_config;
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        let game_stats = &mut ctx.accounts.game_stats;
+        let payer = &ctx.accounts.payer;
+        let mut player_list = ctx.accounts.player_list.load_mut()?;
+        let total_won_points = &mut ctx.accounts.total_won_points;
+        let config = &table.config;
+        let difficulty = &mut ctx.accounts.difficulty;
+        let yield_accumulator = &ctx.accounts.yield_accumulator;
+        open_vesting(vesting, payer.key());
+
+        // Private tables only admit players the authority has seated
+        if config.private && ctx.accounts.seat.is_none() {
+            return Err(ErrorCode::NotSeated.into());
+        }
+
+        let entry_cost = config.entry_cost as i64;
+        if user.points <= entry_cost {
+            return Err(ProgramError::InsufficientFunds.into());
+        }
+
+        let user_number = pubkey_to_number(&user.key());
+        let slot = Clock::get()?.slot;
+        let power_up = table_power_up(config, user.last_won_slot, slot);
+        let recent_players_sum = recent_players_sum(&player_list);
+        let unix_time = Clock::get()?.unix_timestamp;
+        let time_number = (unix_time % 100_000) as u64;
+        let number_to_test = slot + user_number as u64 + recent_players_sum + time_number;
+
+        accrue_yield(user, yield_accumulator);
+        spend_round_points(user, total_won_points, jackpot, entry_cost)?;
+
+        // Tables pass the same difficulty filter as global rounds, a cheap entry cost buys no easier wins
+        let won = meets_difficulty(number_to_test, difficulty.value) && is_prime(number_to_test, 5);
+        if won {
+            msg!("Table {}: Slot {} + User number {} + Players sum {} + Time number {} = {} is prime at difficulty {}.", table_id, slot, user_number, recent_players_sum, time_number, number_to_test, difficulty.value);
+            let superprime = config.superprime_digits > 0 && has_suffix(number_to_test, config.superprime_suffix, config.superprime_digits);
+            let reward_points = settle_prime(user, jackpot, treasury, vesting, vesting_config, fee_vault, game_stats, None, None, payer, total_won_points, slot, number_to_test, power_up, superprime, PayoutCap::FULL)?;
+
+            // Table jackpots are filled by entry costs the authority sets, so the reward is spendable but never shares the global yield
+            withhold_from_yield(user, total_won_points, reward_points)?;
+            user.points += reward_points;
+            record_win(difficulty, slot);
+        } else {
+            msg!("Table {}: Slot {} + User number {} + Players sum {} + Time number {} = {} did not win. Jackpot pool increased by {} points.", table_id, slot, user_number, recent_players_sum, time_number, number_to_test, entry_cost);
+        }
+        sync_reward_debt(user, yield_accumulator);
+        record_play(user, slot, 1, won);
+        record_game_rounds(game_stats, 1);
+
+        player_list.push(payer.key(), slot);
+
+        msg!("User {} now has {} points.", payer.key(), user.points);
+        msg!("Table {} jackpot pool now has {} points.", table_id, jackpot.amount);
+
+        Ok(())
+    }
+
+    pub fn close_table(ctx: Context<CloseTable>, table_id: u64) -> Result<()> {
+        // Anyone can fund a table, so the authority may only close it once players have won the treasury down to rent
+        let treasury = ctx.accounts.treasury.to_account_info();
+        let rent_exemption = Rent::get()?.minimum_balance(treasury.data_len());
+        if treasury.lamports() > rent_exemption {
+            return Err(ErrorCode::TableTreasuryNotEmpty.into());
+        }
+
+        msg!("Table {} closed, rent returned to {}", table_id, ctx.accounts.authority.key());
+        Ok(())
+    }
+
+    pub fn create_guild(ctx: Context<CreateGuild>, guild_id: u64, window_slots: u64) -> Result<()> {
+        ctx.accounts.guild.version = Guild::VERSION;
+        let guild = &mut ctx.accounts.guild;
+
+        if window_slots == 0 {
+            return Err(ErrorCode::InvalidGuildWindow.into());
+        }
+        guild.guild_id = guild_id;
+        guild.founder = ctx.accounts.payer.key();
+        guild.window_slots = window_slots;
+
+        msg!("Guild {} created by {} with {} slot reward windows.", guild_id, guild.founder, window_slots);
+        Ok(())
+    }
+
+    pub fn join_guild(ctx: Context<JoinGuild>, guild_id: u64) -> Result<()> {
+        ctx.accounts.guild_member.version = GuildMember::VERSION;
+        let guild = &mut ctx.accounts.guild;
+        let member = &mut ctx.accounts.guild_member;
+
+        member.guild_id = guild_id;
+        member.player = ctx.accounts.payer.key();
+        member.joined_slot = Clock::get()?.slot;
+        guild.member_count += 1;
+
+        msg!("Player {} joined guild {}, now {} members.", member.player, guild_id, guild.member_count);
+        Ok(())
+    }
+
+    pub fn leave_guild(ctx: Context<LeaveGuild>, guild_id: u64) -> Result<()> {
+        let guild = &mut ctx.accounts.guild;
+        let member = &ctx.accounts.guild_member;
+
+        // Contributions have to be claimed first, and hopping in just for a win is ruled out by the cooldown
+        if member.contribution > 0 {
+            return Err(ErrorCode::GuildRewardsUnclaimed.into());
+        }
+        if Clock::get()?.slot < member.joined_slot + GUILD_LEAVE_COOLDOWN_SLOTS {
+            return Err(ErrorCode::GuildLeaveCooldown.into());
+        }
+        guild.member_count -= 1;
+
+        msg!("Player {} left guild {}, now {} members.", member.player, guild_id, guild.member_count);
+        Ok(())
+    }
+
+    pub fn initialize_guild_leaderboard(ctx: Context<InitializeGuildLeaderboard>) -> Result<()> {
+        ctx.accounts.guild_leaderboard.version = GuildLeaderboard::VERSION;
+        msg!("Guild leaderboard initialized {}", ctx.accounts.guild_leaderboard.key());
+        Ok(())
+    }
+
+    pub fn check_guild_slot(ctx: Context<CheckGuildSlot>, guild_id: u64, window_id: u64) -> Result<()> {
+        ctx.accounts.guild_window.version = GuildWindow::VERSION;
+        let game = &mut ctx.accounts.game;
+        let guild = &mut ctx.accounts.guild;
+        let member = &mut ctx.accounts.guild_member;
+        let guild_window = &mut ctx.accounts.guild_window;
+        let user = &mut game.user;
+        let jackpot = &mut game.jackpot;
+        let treasury = &mut game.treasury;
+        let vesting = &mut game.vesting;
+        let vesting_config = &game.vesting_config;
+        let fee_vault = &mut game.fee_vault;
+        let game_stats = &mut game.game_stats;
+        let mut win_history = game.win_history.load_mut()?;
+        let season = &mut game.season;
+        let payer = &game.payer;
+        let mut player_list = game.player_list.load_mut()?;
+        let total_won_points = &mut game.total_won_points;
+        let difficulty = &mut game.difficulty;
+        let epoch = &mut game.epoch;
+        let yield_accumulator = &game.yield_accumulator;
+        open_vesting(vesting, payer.key());
+
+        if user.points <= ROUND_COST {
+            return Err(ProgramError::InsufficientFunds.into());
+        }
+
+        let slot = Clock::get()?.slot;
+        if window_id != slot / guild.window_slots {
+            return Err(ErrorCode::WrongGuildWindow.into());
+        }
+        // A member carries one window of contribution at a time
+        if member.window_id != window_id {
+            if member.contribution > 0 {
+                return Err(ErrorCode::GuildRewardsUnclaimed.into());
+            }
+            member.window_id = window_id;
+        }
+        guild_window.guild_id = guild_id;
+        guild_window.window_id = window_id;
+
+        // The power-up is shared, so any member's win resets it for the whole guild
+        let user_number = pubkey_to_number(&user.key());
+        let power_up = power_up_for(guild.last_won_slot, slot);
+        let recent_players_sum = recent_players_sum(&player_list);
+        let unix_time = Clock::get()?.unix_timestamp;
+        let time_number = (unix_time % 100_000) as u64;
+        let number_to_test = slot + user_number as u64 + recent_players_sum + time_number;
+
+        accrue_yield(user, yield_accumulator);
+        spend_round_points(user, total_won_points, jackpot, ROUND_COST)?;
+        record_epoch_rounds(epoch, slot, 1);
+        member.contribution += ROUND_COST as u64;
+        guild_window.total_contribution += ROUND_COST as u64;
+
+        let won = meets_difficulty(number_to_test, difficulty.value) && is_prime(number_to_test, 5);
+        if won {
+            msg!("Guild {}: Slot {} + User number {} + Players sum {} + Time number {} = {} is prime at difficulty {}.", guild_id, slot, user_number, recent_players_sum, time_number, number_to_test, difficulty.value);
+            let superprime = has_suffix(number_to_test, SUPERPRIME_SUFFIX, SUPERPRIME_DIGITS);
+            let reward_points = settle_prime(user, jackpot, treasury, vesting, vesting_config, fee_vault, game_stats, Some(&mut win_history), Some(&mut *season), payer, total_won_points, slot, number_to_test, power_up, superprime, PayoutCap::FULL)?;
+
+            // Won points go to the window pool and are split by contribution once it closes; SOL stays with the finder.
+            // They earn no yield while pooled and rejoin the total when members claim them.
+            withhold_from_yield(user, total_won_points, reward_points)?;
+            guild_window.pool += reward_points as u64;
+            guild.total_won_points += reward_points as u64;
+            guild.last_won_slot = slot;
+            update_guild_leaderboard(&mut ctx.accounts.guild_leaderboard, guild_id, guild.total_won_points);
+            record_win(difficulty, slot);
+            record_epoch_win(epoch, payer.key(), slot);
+        } else {
+            msg!("Guild {}: Slot {} + User number {} + Players sum {} + Time number {} = {} is not prime. Jackpot pool increased by 10 points.", guild_id, slot, user_number, recent_players_sum, time_number, number_to_test);
+        }
+        sync_reward_debt(user, yield_accumulator);
+        record_play(user, slot, 1, won);
+        record_game_rounds(game_stats, 1);
+
+        player_list.push(payer.key(), slot);
+
+        msg!("Guild {} window {} pool now has {} points from {} contributed.", guild_id, window_id, guild_window.pool, guild_window.total_contribution);
+        Ok(())
+    }
+
+    pub fn claim_guild_rewards(ctx: Context<ClaimGuildRewards>, guild_id: u64, window_id: u64) -> Result<()> {
+        let user = &mut ctx.accounts.user;
+        let member = &mut ctx.accounts.guild_member;
+        let guild_window = &mut ctx.accounts.guild_window;
+        let yield_accumulator = &ctx.accounts.yield_accumulator;
+
+        if member.window_id != window_id || member.contribution == 0 {
+            return Err(ErrorCode::WrongGuildWindow.into());
+        }
+        if Clock::get()?.slot / ctx.accounts.guild.window_slots <= window_id {
+            return Err(ErrorCode::GuildWindowOpen.into());
+        }
+
+        let share = (guild_window.pool as u128 * member.contribution as u128 / guild_window.total_contribution as u128) as u64;
+        accrue_yield(user, yield_accumulator);
+        user.won_points += share as i64;
+        user.points += share as i64;
+        sync_reward_debt(user, yield_accumulator);
+        // Claimed pool points start earning yield again
+        let total_won_points = &mut ctx.accounts.total_won_points;
+        total_won_points.points = total_won_points.points.checked_add(share).ok_or(ErrorCode::YieldOverflow)?;
+        guild_window.claimed += share;
+        member.contribution = 0;
+
+        msg!("Player {} claimed {} won points from guild {} window {}.", member.player, share, guild_id, window_id);
+        Ok(())
+    }
+
+    pub fn initialize_points_mint(ctx: Context<InitializePointsMint>) -> Result<()> {
+        msg!("Points mint initialized {} with authority {}", ctx.accounts.points_mint.key(), ctx.accounts.mint_authority.key());
+        Ok(())
+    }
+
+    pub fn pay_for_point_tokens(ctx: Context<PayForPointTokens>, quantity: u64, max_lamports: u64) -> Result<()> {
+        let purchase = &mut ctx.accounts.purchase;
+
+        let lamports = quote_points(&purchase.pricing_config, quantity)?;
+        if lamports > max_lamports {
+            return Err(ErrorCode::SlippageExceeded.into());
+        }
+
+        let referral_cut = pay_referral(&mut purchase.user, &mut purchase.referrer_stats, &mut purchase.referral_vault, &purchase.payer, &purchase.system_program, lamports)?;
+        let staking_deposit = collect_point_payment(&purchase.payer, &purchase.treasury, &purchase.staking_treasury, &mut purchase.fee_vault, &mut purchase.season_state, &purchase.system_program, lamports - referral_cut)?;
+
+        distribute_yield(&mut purchase.yield_accumulator, purchase.total_won_points.points, staking_deposit)?;
+        purchase.game_stats.total_deposited += lamports;
+
+        mint_point_tokens(
+            &ctx.accounts.points_mint,
+            &ctx.accounts.user_points_account,
+            &ctx.accounts.mint_authority,
+            &ctx.accounts.token_program,
+            ctx.bumps.mint_authority,
+            quantity,
+        )?;
+
+        msg!("User {} paid {} lamports and received {} POINTS tokens.", purchase.payer.key(), lamports, quantity);
+        msg!("Buy Points: Yield Pool {}",  **purchase.staking_treasury.to_account_info().lamports.borrow());
+
+        Ok(())
+    }
+
+    pub fn check_slot_tokens(ctx: Context<CheckSlotTokens>) -> Result<()> {
+        let game = &mut ctx.accounts.game;
+        let user = &mut game.user;
+        let jackpot = &mut game.jackpot;
+        let treasury = &mut game.treasury;
+        let vesting = &mut game.vesting;
+        let vesting_config = &game.vesting_config;
+        let fee_vault = &mut game.fee_vault;
+        let game_stats = &mut game.game_stats;
+        let mut win_history = game.win_history.load_mut()?;
+        let season = &mut game.season;
+        let payer = &game.payer;
+        let mut player_list = game.player_list.load_mut()?;
+        let total_won_points = &mut game.total_won_points;
+        let difficulty = &mut game.difficulty;
+        let epoch = &mut game.epoch;
+        let yield_accumulator = &game.yield_accumulator;
+        open_vesting(vesting, payer.key());
+
+        // Same 10 point reserve as check_slot, checked against the token balance
+        let balance = ctx.accounts.user_points_account.amount as i64;
+        if balance <= ROUND_COST {
+            return Err(ProgramError::InsufficientFunds.into());
+        }
+
+        let user_number = pubkey_to_number(&user.key());
+        let slot = Clock::get()?.slot;
+        let power_up = power_up_for(user.last_won_slot, slot);
+        let recent_players_sum = recent_players_sum(&player_list);
+        let unix_time = Clock::get()?.unix_timestamp;
+        let time_number = (unix_time % 100_000) as u64;
+        let number_to_test = slot + user_number as u64 + recent_players_sum + time_number;
+
+        // Burn the round cost instead of debiting User.points; won points are backed by both balances
+        let backing_balance = balance + user.points.max(0);
+        accrue_yield(user, yield_accumulator);
+        spend_won_points_first(user, total_won_points, backing_balance, ROUND_COST)?;
+        jackpot.amount += ROUND_COST;
+        token_interface::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.points_mint.to_account_info(),
+                    from: ctx.accounts.user_points_account.to_account_info(),
+                    authority: payer.to_account_info(),
+                },
+            ),
+            ROUND_COST as u64,
+        )?;
+        record_epoch_rounds(epoch, slot, 1);
+
+        let won = meets_difficulty(number_to_test, difficulty.value) && is_prime(number_to_test, 5);
+        if won {
+            msg!("Slot {} + User number {} + Players sum {} + Time number {} = {} is prime at difficulty {}.", slot, user_number, recent_players_sum, time_number, number_to_test, difficulty.value);
+            let superprime = has_suffix(number_to_test, SUPERPRIME_SUFFIX, SUPERPRIME_DIGITS);
+            // Credited internally like any other win, so won points never become transferable tokens
+            award_prime(user, jackpot, treasury, vesting, vesting_config, fee_vault, game_stats, Some(&mut win_history), Some(&mut *season), payer, total_won_points, slot, number_to_test, power_up, superprime, PayoutCap::FULL)?;
+            record_win(difficulty, slot);
+            record_epoch_win(epoch, payer.key(), slot);
+        } else {
+            msg!("Slot {} + User number {} + Players sum {} + Time number {} = {} is not prime. Jackpot pool increased by 10 points.", slot, user_number, recent_players_sum, time_number, number_to_test);
+        }
+        sync_reward_debt(user, yield_accumulator);
+        record_play(user, slot, 1, won);
+        record_game_rounds(game_stats, 1);
+
+        player_list.push(payer.key(), slot);
+
+        msg!("Jackpot pool now has {} points.", jackpot.amount);
+        msg!("User {} has {} won points.", payer.key(), user.won_points);
+
+        Ok(())
+    }
+
+    pub fn migrate_points_to_tokens(ctx: Context<MigratePointsToTokens>) -> Result<()> {
+        ctx.accounts.token_migration.version = TokenMigration::VERSION;
+        let user = &mut ctx.accounts.user;
+
+        // Won points stay locked in User.points, a yield-bearing balance must not leave as transferable tokens
+        let locked = user.points.clamp(0, user.won_points.max(0));
+        let points = (user.points.max(0) - locked) as u64;
+        mint_point_tokens(
+            &ctx.accounts.points_mint,
+            &ctx.accounts.user_points_account,
+            &ctx.accounts.mint_authority,
+            &ctx.accounts.token_program,
+            ctx.bumps.mint_authority,
+            points,
+        )?;
+        user.points = locked;
+
+        msg!("User {} migrated {} points to POINTS tokens, {} won points stay on their account.", ctx.accounts.payer.key(), points, locked);
+        Ok(())
+    }
+
+    pub fn mint_badge(ctx: Context<MintBadge>, achievement: u8) -> Result<()> {
+        if achievement >= ACHIEVEMENT_COUNT {
+            return Err(ErrorCode::InvalidAchievement.into());
+        }
+        let user = &mut ctx.accounts.user;
+        let bit = 1u32 << achievement;
+        if user.achievements & bit == 0 {
+            return Err(ErrorCode::AchievementLocked.into());
+        }
+        if user.badges_minted & bit != 0 {
+            return Err(ErrorCode::BadgeAlreadyMinted.into());
+        }
+
+        let payer = &ctx.accounts.payer;
+        let badge_mint = &ctx.accounts.badge_mint;
+        let token_program = &ctx.accounts.token_program;
+
+        // The non-transferable extension has to be set up before the mint itself, which
+        // Anchor's mint constraints can't do, so the badge mint is created by hand
+        let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[ExtensionType::NonTransferable])?;
+        let payer_key = payer.key();
+        let badge_seeds: &[&[u8]] = &[b"badge", payer_key.as_ref(), &[achievement], &[ctx.bumps.badge_mint]];
+        let create_instruction = anchor_lang::solana_program::system_instruction::create_account(
+            &payer.key(),
+            &badge_mint.key(),
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            &token_program.key(),
+        );
+        anchor_lang::solana_program::program::invoke_signed(
+            &create_instruction,
+            &[payer.to_account_info(), badge_mint.to_account_info()],
+            &[badge_seeds],
+        )?;
+        token_interface::non_transferable_mint_initialize(CpiContext::new(
+            token_program.to_account_info(),
+            token_interface::NonTransferableMintInitialize {
+                token_program_id: token_program.to_account_info(),
+                mint: badge_mint.to_account_info(),
+            },
+        ))?;
+        token_interface::initialize_mint2(
+            CpiContext::new(
+                token_program.to_account_info(),
+                token_interface::InitializeMint2 { mint: badge_mint.to_account_info() },
+            ),
+            0,
+            &ctx.accounts.mint_authority.key(),
+            None,
+        )?;
+
+        associated_token::create(CpiContext::new(
+            ctx.accounts.associated_token_program.to_account_info(),
+            associated_token::Create {
+                payer: payer.to_account_info(),
+                associated_token: ctx.accounts.badge_account.to_account_info(),
+                authority: payer.to_account_info(),
+                mint: badge_mint.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: token_program.to_account_info(),
+            },
+        ))?;
+
+        // Mint the single badge, then drop the mint authority so the supply stays at one
+        let signer_seeds: &[&[&[u8]]] = &[&[b"mint_authority", &[ctx.bumps.mint_authority]]];
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                MintTo {
+                    mint: badge_mint.to_account_info(),
+                    to: ctx.accounts.badge_account.to_account_info(),
+                    authority: ctx.accounts.mint_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            1,
+        )?;
+        token_interface::set_authority(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                token_interface::SetAuthority {
+                    current_authority: ctx.accounts.mint_authority.to_account_info(),
+                    account_or_mint: badge_mint.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            AuthorityType::MintTokens,
+            None,
+        )?;
+
+        user.badges_minted |= bit;
+        emit!(BadgeMinted { player: payer.key(), achievement, mint: badge_mint.key() });
+        msg!("User {} minted badge {} for achievement {}.", payer.key(), badge_mint.key(), achievement);
+        Ok(())
+    }
+
+    pub fn initialize_payment_config(ctx: Context<InitializePaymentConfig>, treasury_bps: u16) -> Result<()> {
+        ctx.accounts.payment_config.version = PaymentConfig::VERSION;
+        let payment_config = &mut ctx.accounts.payment_config;
+
+        // The first initializer becomes the admin allowed to manage accepted mints
+        if payment_config.admin == Pubkey::default() {
+            if treasury_bps > 10_000 {
+                return Err(ErrorCode::InvalidPaymentConfig.into());
+            }
+            payment_config.admin = ctx.accounts.payer.key();
+            payment_config.treasury_bps = treasury_bps;
+            payment_config.accepted_mints = Vec::new();
+            msg!("Payment config initialized, {} bps of SPL purchases go to the treasury.", treasury_bps);
+        } else {
+            msg!("Payment config already initialized {}", payment_config.key());
+        }
+
+        Ok(())
+    }
+
+    pub fn set_accepted_mint(ctx: Context<SetAcceptedMint>, price_per_1000: u64) -> Result<()> {
+        let payment_config = &mut ctx.accounts.payment_config;
+        let mint = ctx.accounts.mint.key();
+
+        if price_per_1000 == 0 {
+            return Err(ErrorCode::InvalidPaymentConfig.into());
+        }
+
+        if let Some(accepted) = payment_config.accepted_mints.iter_mut().find(|m| m.mint == mint) {
+            accepted.price_per_1000 = price_per_1000;
+        } else if payment_config.accepted_mints.len() < MAX_ACCEPTED_MINTS {
+            payment_config.accepted_mints.push(AcceptedMint { mint, price_per_1000 });
+        } else {
+            return Err(ErrorCode::TooManyAcceptedMints.into());
+        }
+
+        // Tokens already in the yield vault go to the first won points put behind this mint
+        let spl_yield_pool = &mut ctx.accounts.spl_yield_pool;
+        if spl_yield_pool.version == 0 {
+            spl_yield_pool.version = SplYieldPool::VERSION;
+            spl_yield_pool.unallocated = ctx.accounts.staking_vault.amount;
+        }
+
+        msg!("Mint {} accepted at {} base units per 1000 points.", mint, price_per_1000);
+        Ok(())
+    }
+
+    pub fn remove_accepted_mint(ctx: Context<RemoveAcceptedMint>, mint: Pubkey) -> Result<()> {
+        let payment_config = &mut ctx.accounts.payment_config;
+        payment_config.accepted_mints.retain(|m| m.mint != mint);
+        msg!("Mint {} no longer accepted.", mint);
+        Ok(())
+    }
+
+    pub fn pay_for_points_spl(ctx: Context<PayForPointsSpl>, quantity: u64, max_amount: u64) -> Result<()> {
+        let user = &mut ctx.accounts.user;
+        let payment_config = &ctx.accounts.payment_config;
+        let mint = &ctx.accounts.mint;
+        let payer = &ctx.accounts.payer;
+
+        let price = payment_config
+            .accepted_mints
+            .iter()
+            .find(|m| m.mint == mint.key())
+            .ok_or(ErrorCode::MintNotAccepted)?
+            .price_per_1000;
+
+        // Same quantity bounds and volume tiers as SOL purchases, at the mint's own price
+        let amount = quote_points_at(&ctx.accounts.pricing_config, price, quantity)?;
+        if amount > max_amount {
+            return Err(ErrorCode::SlippageExceeded.into());
+        }
+
+        // Referrer cut, then the protocol fee, then the treasury / yield pool split, all in the paid mint.
+        // The season prize pool is funded in SOL only, so SPL purchases don't feed it.
+        let referral_bps = bind_referrer(user, &mut ctx.accounts.referrer_stats, &ctx.accounts.referral_vault, payer)?;
+        let referral_cut = amount * referral_bps as u64 / 10_000;
+        let fee = (amount - referral_cut) * ctx.accounts.fee_vault.purchase_fee_bps as u64 / 10_000;
+        let treasury_share = ((amount - referral_cut - fee) as u128 * payment_config.treasury_bps as u128 / 10_000) as u64;
+        let staking_share = amount - referral_cut - fee - treasury_share;
+
+        if referral_cut > 0 {
+            let referrer_token_account = ctx.accounts.referrer_token_account.as_ref().ok_or(ErrorCode::ReferrerMismatch)?;
+            if referrer_token_account.owner != user.referrer {
+                return Err(ErrorCode::ReferrerMismatch.into());
+            }
+            transfer_spl_payment(&ctx.accounts.payer_token_account, referrer_token_account, mint, payer, &ctx.accounts.token_program, referral_cut)?;
+        }
+        for (vault, share) in [(&ctx.accounts.fee_token_vault, fee), (&ctx.accounts.treasury_vault, treasury_share), (&ctx.accounts.staking_vault, staking_share)] {
+            transfer_spl_payment(&ctx.accounts.payer_token_account, vault, mint, payer, &ctx.accounts.token_program, share)?;
+        }
+        // Recorded like a SOL purchase fee, in the paid mint's own units
+        if fee > 0 {
+            record_fee(&mut ctx.accounts.fee_vault, payer.key(), fee, false);
+        }
+        let spl_yield_pool: &mut SplYieldPool = &mut ctx.accounts.spl_yield_pool;
+        let enrolled_points = spl_yield_pool.enrolled_points;
+        accumulate(&mut spl_yield_pool.acc_reward_per_point, &mut spl_yield_pool.unallocated, enrolled_points as u128, staking_share)?;
+
+        // Add points to user
+        user.points += quantity as i64;
+        msg!("User {} paid {} of mint {} and received {} points.", payer.key(), amount, mint.key(), quantity);
+        msg!("Buy Points: Referrer +{} Fees +{} Treasury vault +{} Yield vault +{}", referral_cut, fee, treasury_share, staking_share);
+
+        Ok(())
+    }
+
+    pub fn enter_spl_yield(ctx: Context<EnterSplYield>, amount: u64) -> Result<()> {
+        let user = &mut ctx.accounts.user;
+        let yield_accumulator = &ctx.accounts.yield_accumulator;
+        let total_won_points = &mut ctx.accounts.total_won_points;
+        let pool = &mut ctx.accounts.spl_yield_pool;
+        let position = &mut ctx.accounts.spl_yield_position;
+
+        if amount == 0 || amount as i64 > user.won_points || amount as i64 > user.points {
+            return Err(ProgramError::InsufficientFunds.into());
+        }
+
+        // The points leave the SOL yield for good, each won point is paid in one asset only
+        accrue_yield(user, yield_accumulator);
+        user.won_points -= amount as i64;
+        user.points -= amount as i64;
+        total_won_points.points = total_won_points.points.checked_sub(amount).ok_or(ErrorCode::TotalWonPointsUnderflow)?;
+        sync_reward_debt(user, yield_accumulator);
+
+        position.version = SplYieldPosition::VERSION;
+        position.owner = ctx.accounts.payer.key();
+        accrue_spl_yield(position, pool);
+        position.amount += amount;
+        pool.enrolled_points += amount;
+        position.reward_debt = position.amount as u128 * pool.acc_reward_per_point / ACC_PRECISION;
+
+        msg!("User {} put {} won points behind mint {}, {} in total.", position.owner, amount, ctx.accounts.mint.key(), position.amount);
+        Ok(())
+    }
+
+    pub fn redeem_won_points_spl(ctx: Context<RedeemWonPointsSpl>, amount: u64, min_tokens_out: u64) -> Result<()> {
+        let accounts = ctx.accounts;
+        let pool = &mut accounts.spl_yield_pool;
+        let position = &mut accounts.spl_yield_position;
+
+        if amount == 0 {
+            return Err(ErrorCode::InvalidRedemption.into());
+        }
+
+        // Same pricing as a SOL redemption, against the yield this mint paid the position
+        accrue_spl_yield(position, pool);
+        let tokens_out = redemption_share(position.pending_tokens, position.amount, amount)?;
+        if tokens_out < min_tokens_out {
+            return Err(ErrorCode::SlippageExceeded.into());
+        }
+
+        position.pending_tokens -= tokens_out;
+        position.amount -= amount;
+        pool.enrolled_points -= amount;
+        position.reward_debt = position.amount as u128 * pool.acc_reward_per_point / ACC_PRECISION;
+
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault_authority", &[ctx.bumps.vault_authority]]];
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: accounts.staking_vault.to_account_info(),
+                    mint: accounts.mint.to_account_info(),
+                    to: accounts.user_token_account.to_account_info(),
+                    authority: accounts.vault_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            tokens_out,
+            accounts.mint.decimals,
+        )?;
+
+        emit!(SplWonPointsRedeemed {
+            user: accounts.payer.key(),
+            mint: accounts.mint.key(),
+            amount,
+            tokens_out,
+            slot: Clock::get()?.slot,
+        });
+        msg!("Redeemed {} won points for {} of mint {} from its yield vault, {} left behind it.", amount, tokens_out, accounts.mint.key(), position.amount);
+        Ok(())
+    }
+
+    pub fn withdraw_spl_vault(ctx: Context<WithdrawSplVault>, amount: u64) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        let mint = &ctx.accounts.mint;
+
+        // Only the treasury and fee vaults, the yield vault belongs to won point holders
+        let treasury_vault = Pubkey::find_program_address(&[b"treasury_vault", mint.key().as_ref()], &crate::ID).0;
+        let fee_token_vault = Pubkey::find_program_address(&[b"fee_token_vault", mint.key().as_ref()], &crate::ID).0;
+        if vault.key() != treasury_vault && vault.key() != fee_token_vault {
+            return Err(ErrorCode::InvalidVault.into());
+        }
+        if amount > vault.amount {
+            return Err(ProgramError::InsufficientFunds.into());
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault_authority", &[ctx.bumps.vault_authority]]];
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: vault.to_account_info(),
+                    mint: mint.to_account_info(),
+                    to: ctx.accounts.recipient.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            mint.decimals,
+        )?;
+
+        msg!("Withdrew {} of mint {} from vault {} to {}.", amount, mint.key(), vault.key(), ctx.accounts.recipient.key());
+        Ok(())
+    }
+
+    pub fn stake_points(ctx: Context<StakePoints>, position_id: u64, amount: u64, lock_slots: u64) -> Result<()> {
+        ctx.accounts.stake_position.version = StakePosition::VERSION;
+        let user = &mut ctx.accounts.user;
+        let position = &mut ctx.accounts.stake_position;
+        let yield_accumulator = &mut ctx.accounts.yield_accumulator;
+        let total_won_points = &mut ctx.accounts.total_won_points;
+
+        let multiplier_bps = stake_multiplier_bps(lock_slots).ok_or(ErrorCode::LockTooShort)?;
+        if amount == 0 || amount as i64 > user.won_points || amount as i64 > user.points {
+            return Err(ProgramError::InsufficientFunds.into());
+        }
+
+        // Staked points leave the liquid balance and earn through the position instead
+        accrue_yield(user, yield_accumulator);
+        user.won_points -= amount as i64;
+        user.points -= amount as i64;
+        total_won_points.points = total_won_points.points.checked_sub(amount).ok_or(ErrorCode::TotalWonPointsUnderflow)?;
+        sync_reward_debt(user, yield_accumulator);
+
+        let slot = Clock::get()?.slot;
+        position.owner = ctx.accounts.payer.key();
+        position.position_id = position_id;
+        position.amount = amount;
+        position.weight = amount * multiplier_bps / 10_000;
+        position.lock_until_slot = slot + lock_slots;
+        position.unstake_requested_slot = 0;
+        position.reward_debt = position.weight as u128 * yield_accumulator.acc_reward_per_point / ACC_PRECISION;
+        yield_accumulator.total_stake_weight += position.weight;
+
+        msg!("User {} staked {} won points in position {} until slot {} at {} bps.", position.owner, amount, position_id, position.lock_until_slot, multiplier_bps);
+        Ok(())
+    }
+
+    pub fn unstake_points(ctx: Context<UnstakePoints>, position_id: u64) -> Result<()> {
+        let user = &mut ctx.accounts.user;
+        let position = &mut ctx.accounts.stake_position;
+        let yield_accumulator = &mut ctx.accounts.yield_accumulator;
+
+        let slot = Clock::get()?.slot;
+        if slot < position.lock_until_slot {
+            return Err(ErrorCode::StakeLocked.into());
+        }
+        if position.unstake_requested_slot != 0 {
+            return Err(ErrorCode::UnstakeAlreadyRequested.into());
+        }
+
+        // Stop earning now, the yield so far is claimable through claim_lamports
+        let earned = accrue_stake_yield(position, yield_accumulator);
+        user.pending_yield += earned;
+        yield_accumulator.total_stake_weight -= position.weight;
+        position.weight = 0;
+        position.reward_debt = 0;
+        position.unstake_requested_slot = slot;
+
+        msg!("Position {} unstaking, {} lamports of yield credited, withdrawable at slot {}.", position_id, earned, slot + UNSTAKE_COOLDOWN_SLOTS);
+        Ok(())
+    }
+
+    pub fn withdraw_stake(ctx: Context<WithdrawStake>, position_id: u64) -> Result<()> {
+        let user = &mut ctx.accounts.user;
+        let position = &ctx.accounts.stake_position;
+        let yield_accumulator = &ctx.accounts.yield_accumulator;
+        let total_won_points = &mut ctx.accounts.total_won_points;
+
+        let slot = Clock::get()?.slot;
+        if position.unstake_requested_slot == 0 || slot < position.unstake_requested_slot + UNSTAKE_COOLDOWN_SLOTS {
+            return Err(ErrorCode::StakeCoolingDown.into());
+        }
+
+        // Points return as liquid won points; the position account is closed to the owner
+        accrue_yield(user, yield_accumulator);
+        user.won_points += position.amount as i64;
+        user.points += position.amount as i64;
+        total_won_points.points += position.amount;
+        sync_reward_debt(user, yield_accumulator);
+
+        msg!("Position {} withdrawn, {} won points returned to {}.", position_id, position.amount, position.owner);
+        Ok(())
+    }
+
+    pub fn initialize_vesting_config(ctx: Context<InitializeVestingConfig>, threshold_lamports: u64, immediate_bps: u16, vesting_slots: u64) -> Result<()> {
+        ctx.accounts.vesting_config.version = VestingConfig::VERSION;
+        let vesting_config = &mut ctx.accounts.vesting_config;
+
+        if vesting_config.admin == Pubkey::default() {
+            validate_vesting_config(immediate_bps, vesting_slots)?;
+            vesting_config.admin = ctx.accounts.payer.key();
+            vesting_config.threshold_lamports = threshold_lamports;
+            vesting_config.immediate_bps = immediate_bps;
+            vesting_config.vesting_slots = vesting_slots;
+            msg!("Vesting config initialized: wins above {} lamports pay {} bps now and vest over {} slots.", threshold_lamports, immediate_bps, vesting_slots);
+        } else {
+            msg!("Vesting config already initialized {}", vesting_config.key());
+        }
+
+        Ok(())
+    }
+
+    pub fn set_vesting_config(ctx: Context<SetVestingConfig>, threshold_lamports: u64, immediate_bps: u16, vesting_slots: u64) -> Result<()> {
+        let vesting_config = &mut ctx.accounts.vesting_config;
+
+        // Existing schedules keep their end slot, only new wins use the new terms
+        validate_vesting_config(immediate_bps, vesting_slots)?;
+        vesting_config.threshold_lamports = threshold_lamports;
+        vesting_config.immediate_bps = immediate_bps;
+        vesting_config.vesting_slots = vesting_slots;
+
+        msg!("Vesting set: wins above {} lamports pay {} bps now and vest over {} slots.", threshold_lamports, immediate_bps, vesting_slots);
+        Ok(())
+    }
+
+    pub fn initialize_vesting(ctx: Context<InitializeVesting>) -> Result<()> {
+        let vesting = &mut ctx.accounts.vesting;
+
+        if vesting.beneficiary == Pubkey::default() {
+            open_vesting(vesting, ctx.accounts.payer.key());
+            msg!("Vesting account initialized for {}", vesting.beneficiary);
+        } else {
+            msg!("Vesting account already initialized {}", vesting.key());
+        }
+
+        Ok(())
+    }
+
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        let vesting = &mut ctx.accounts.vesting;
+        let payer = &ctx.accounts.payer;
+
+        let slot = Clock::get()?.slot;
+        let released = vested_by(vesting, slot);
+        let claimable = vesting.unclaimed + released - vesting.claimed;
+        if claimable == 0 {
+            return Err(ErrorCode::NothingVested.into());
+        }
+
+        // The vesting account holds exactly the locked lamports on top of its rent
+        **vesting.to_account_info().lamports.borrow_mut() -= claimable;
+        **payer.to_account_info().lamports.borrow_mut() += claimable;
+        vesting.unclaimed = 0;
+        vesting.claimed = released;
+        ctx.accounts.user.lamports_claimed += claimable;
+
+        msg!("User {} claimed {} vested lamports, {} still locked until slot {}.", payer.key(), claimable, vesting.amount - released, vesting.end_slot);
+        Ok(())
+    }
+
+    pub fn initialize_fee_vault(ctx: Context<InitializeFeeVault>, purchase_fee_bps: u16, win_fee_bps: u16) -> Result<()> {
+        ctx.accounts.fee_vault.version = FeeVault::VERSION;
+        let fee_vault = &mut ctx.accounts.fee_vault;
+
+        if fee_vault.admin == Pubkey::default() {
+            validate_fee_bps(purchase_fee_bps, win_fee_bps)?;
+            fee_vault.admin = ctx.accounts.payer.key();
+            fee_vault.purchase_fee_bps = purchase_fee_bps;
+            fee_vault.win_fee_bps = win_fee_bps;
+            msg!("Fee vault initialized: {} bps on purchases, {} bps on wins.", purchase_fee_bps, win_fee_bps);
+        } else {
+            msg!("Fee vault already initialized {}", fee_vault.key());
+        }
+
+        Ok(())
+    }
+
+    pub fn set_fees(ctx: Context<SetFees>, purchase_fee_bps: u16, win_fee_bps: u16) -> Result<()> {
+        let fee_vault = &mut ctx.accounts.fee_vault;
+
+        validate_fee_bps(purchase_fee_bps, win_fee_bps)?;
+        fee_vault.purchase_fee_bps = purchase_fee_bps;
+        fee_vault.win_fee_bps = win_fee_bps;
+
+        msg!("Fees set to {} bps on purchases, {} bps on wins.", purchase_fee_bps, win_fee_bps);
+        Ok(())
+    }
+
+    pub fn withdraw_fees(ctx: Context<WithdrawFees>, amount: u64) -> Result<()> {
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        let recipient = &ctx.accounts.recipient;
+
+        let rent_exemption = Rent::get()?.minimum_balance(fee_vault.to_account_info().data_len());
+        let available = fee_vault.to_account_info().lamports().saturating_sub(rent_exemption);
+        if amount > available {
+            return Err(ProgramError::InsufficientFunds.into());
+        }
+
+        **fee_vault.to_account_info().lamports.borrow_mut() -= amount;
+        **recipient.to_account_info().lamports.borrow_mut() += amount;
+        fee_vault.withdrawn += amount;
+
+        emit!(FeesWithdrawn {
+            recipient: recipient.key(),
+            amount,
+            total_withdrawn: fee_vault.withdrawn,
+        });
+        msg!("Withdrew {} lamports of fees to {}, {} left in the vault.", amount, recipient.key(), available - amount);
+        Ok(())
+    }
+
+    pub fn initialize_referral_vault(ctx: Context<InitializeReferralVault>, referral_bps: u16) -> Result<()> {
+        ctx.accounts.referral_vault.version = ReferralVault::VERSION;
+        let referral_vault = &mut ctx.accounts.referral_vault;
+
+        if referral_vault.admin == Pubkey::default() {
+            if referral_bps > MAX_REFERRAL_BPS {
+                return Err(ErrorCode::ReferralTooHigh.into());
+            }
+            referral_vault.admin = ctx.accounts.payer.key();
+            referral_vault.referral_bps = referral_bps;
+            msg!("Referral vault initialized at {} bps of referred purchases.", referral_bps);
+        } else {
+            msg!("Referral vault already initialized {}", referral_vault.key());
+        }
+
+        Ok(())
+    }
+
+    pub fn set_referral_bps(ctx: Context<SetReferralBps>, referral_bps: u16) -> Result<()> {
+        if referral_bps > MAX_REFERRAL_BPS {
+            return Err(ErrorCode::ReferralTooHigh.into());
+        }
+        ctx.accounts.referral_vault.referral_bps = referral_bps;
+
+        msg!("Referrers now earn {} bps of referred purchases.", referral_bps);
+        Ok(())
+    }
+
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        ctx.accounts.referrer_stats.version = ReferrerStats::VERSION;
+        let referrer_stats = &mut ctx.accounts.referrer_stats;
+
+        referrer_stats.referrer = ctx.accounts.payer.key();
+        msg!("Referrer {} registered.", referrer_stats.referrer);
+        Ok(())
+    }
+
+    pub fn claim_referral_rewards(ctx: Context<ClaimReferralRewards>) -> Result<()> {
+        let referrer_stats = &mut ctx.accounts.referrer_stats;
+        let referral_vault = &mut ctx.accounts.referral_vault;
+        let payer = &ctx.accounts.payer;
+
+        let amount = referrer_stats.earned - referrer_stats.claimed;
+        if amount == 0 {
+            return Err(ErrorCode::NoReferralRewards.into());
+        }
+
+        **referral_vault.to_account_info().lamports.borrow_mut() -= amount;
+        **payer.to_account_info().lamports.borrow_mut() += amount;
+        referrer_stats.claimed += amount;
+        referral_vault.total_claimed += amount;
+        record_game_payout(&mut ctx.accounts.game_stats, amount);
+
+        msg!("Referrer {} claimed {} lamports from {} referred purchases.", payer.key(), amount, referrer_stats.referred_purchases);
+        Ok(())
+    }
+
+    pub fn initialize_transfer_config(ctx: Context<InitializeTransferConfig>, won_points_transferable: bool, daily_cap: u64) -> Result<()> {
+        ctx.accounts.transfer_config.version = TransferConfig::VERSION;
+        let transfer_config = &mut ctx.accounts.transfer_config;
+
+        if transfer_config.admin == Pubkey::default() {
+            transfer_config.admin = ctx.accounts.payer.key();
+            transfer_config.won_points_transferable = won_points_transferable;
+            transfer_config.daily_cap = daily_cap;
+            msg!("Transfer config initialized: won points transferable {}, {} points per day.", won_points_transferable, daily_cap);
+        } else {
+            msg!("Transfer config already initialized {}", transfer_config.key());
+        }
+
+        Ok(())
+    }
+
+    pub fn set_transfer_config(ctx: Context<SetTransferConfig>, won_points_transferable: bool, daily_cap: u64) -> Result<()> {
+        let transfer_config = &mut ctx.accounts.transfer_config;
+
+        transfer_config.won_points_transferable = won_points_transferable;
+        transfer_config.daily_cap = daily_cap;
+
+        msg!("Transfers set: won points transferable {}, {} points per day.", won_points_transferable, daily_cap);
+        Ok(())
+    }
+
+    pub fn transfer_points(ctx: Context<TransferPoints>, amount: u64, recipient: Pubkey) -> Result<()> {
+        let sender = &mut ctx.accounts.user;
+        let receiver = &mut ctx.accounts.recipient_user;
+        let transfer_config = &ctx.accounts.transfer_config;
+        let yield_accumulator = &ctx.accounts.yield_accumulator;
+        let payer = &ctx.accounts.payer;
+
+        if recipient == payer.key() {
+            return Err(ErrorCode::SelfTransfer.into());
+        }
+        if amount == 0 || amount as i64 > sender.points {
+            return Err(ProgramError::InsufficientFunds.into());
+        }
+
+        // Bought points go first, won points only make up the rest when the config allows it
+        let bought_points = (sender.points - sender.won_points.max(0)).max(0) as u64;
+        let won_amount = amount.saturating_sub(bought_points);
+        if won_amount > 0 && !transfer_config.won_points_transferable {
+            return Err(ErrorCode::WonPointsNotTransferable.into());
+        }
+
+        // Cap how much a sender can move per day to keep yield farming through transfers in check
+        let day = Clock::get()?.slot / SLOTS_PER_DAY;
+        if sender.transfer_day != day {
+            sender.transfer_day = day;
+            sender.transferred_today = 0;
+        }
+        if sender.transferred_today + amount > transfer_config.daily_cap {
+            return Err(ErrorCode::DailyTransferCapExceeded.into());
+        }
+        sender.transferred_today += amount;
+
+        // Won points keep earning yield for whoever holds them
+        accrue_yield(sender, yield_accumulator);
+        accrue_yield(receiver, yield_accumulator);
+        sender.points -= amount as i64;
+        sender.won_points -= won_amount as i64;
+        receiver.points += amount as i64;
+        receiver.won_points += won_amount as i64;
+        sync_reward_debt(sender, yield_accumulator);
+        sync_reward_debt(receiver, yield_accumulator);
+
+        emit!(PointsTransferred {
+            from: payer.key(),
+            to: recipient,
+            amount,
+            won_points: won_amount,
+        });
+        msg!("User {} sent {} points ({} won) to {}", payer.key(), amount, won_amount, recipient);
+        Ok(())
+    }
+
+    pub fn trade_won_points(ctx: Context<TradeWonPoints>, _bump: u8, min_lamports_out: u64) -> Result<()> {
+        let accounts = ctx.accounts;
+        let tokens = point_tokens(&accounts.points_mint, &accounts.user_points_account, &accounts.token_program)?;
+
+        // Same price as any other redemption, for a fixed lot of 1000 won points
+        redeem(&mut accounts.user, &mut accounts.total_won_points, &mut accounts.staking_treasury, &accounts.yield_accumulator, &mut accounts.game_stats, &accounts.payer, tokens, 1000, min_lamports_out)?;
+
+        Ok(())
+    }
+
+    pub fn redeem_won_points(ctx: Context<RedeemWonPoints>, amount: u64, min_lamports_out: u64) -> Result<()> {
+        let accounts = ctx.accounts;
+
+        if amount == 0 {
+            return Err(ErrorCode::InvalidRedemption.into());
+        }
+        let tokens = point_tokens(&accounts.points_mint, &accounts.user_points_account, &accounts.token_program)?;
+        redeem(&mut accounts.user, &mut accounts.total_won_points, &mut accounts.staking_treasury, &accounts.yield_accumulator, &mut accounts.game_stats, &accounts.payer, tokens, amount, min_lamports_out)?;
+
+        Ok(())
+    }
+
+    // Lamports redeem_won_points would pay for `amount` won points right now
+    pub fn quote_redemption(ctx: Context<QuoteRedemption>, amount: u64) -> Result<u64> {
+        let mut user = (**ctx.accounts.user).clone();
+        accrue_yield(&mut user, &ctx.accounts.yield_accumulator);
+        quote_redemption_lamports(&user, amount)
+    }
+
+    pub fn initialize_pricing_config(ctx: Context<InitializePricingConfig>) -> Result<()> {
+        ctx.accounts.pricing_config.version = PricingConfig::VERSION;
+        let pricing_config = &mut ctx.accounts.pricing_config;
+
+        // Start from the original fixed price: 1 SOL per 1000 points, no discounts
+        if pricing_config.admin == Pubkey::default() {
+            pricing_config.admin = ctx.accounts.payer.key();
+            pricing_config.lamports_per_1000 = 1_000_000_000;
+            pricing_config.min_quantity = 1000;
+            pricing_config.max_quantity = 100_000;
+            pricing_config.volume_tiers = Vec::new();
+            msg!("Pricing config initialized at {} lamports per 1000 points.", pricing_config.lamports_per_1000);
+        } else {
+            msg!("Pricing config already initialized {}", pricing_config.key());
+        }
+
+        Ok(())
+    }
+
+    pub fn set_pricing(ctx: Context<SetPricing>, params: PricingParams) -> Result<()> {
+        let pricing_config = &mut ctx.accounts.pricing_config;
+
+        validate_pricing(&params)?;
+        pricing_config.lamports_per_1000 = params.lamports_per_1000;
+        pricing_config.min_quantity = params.min_quantity;
+        pricing_config.max_quantity = params.max_quantity;
+        pricing_config.volume_tiers = params.volume_tiers;
+
+        msg!("Pricing set to {} lamports per 1000 points, {} to {} points per purchase, {} volume tiers.",
+            pricing_config.lamports_per_1000,
+            pricing_config.min_quantity,
+            pricing_config.max_quantity,
+            pricing_config.volume_tiers.len()
+        );
+        Ok(())
+    }
+
+    pub fn pay_for_points(ctx: Context<PayForPoints>, quantity: u64, max_lamports: u64) -> Result<()> {
+        let user = &mut ctx.accounts.user;
+        let treasury = &ctx.accounts.treasury;
+        let staking_treasury = &ctx.accounts.staking_treasury;
+        let payer = &ctx.accounts.payer;
+
+        // Refuse if a pricing change between signing and landing raised the cost
+        let lamports = quote_points(&ctx.accounts.pricing_config, quantity)?;
+        if lamports > max_lamports {
+            return Err(ErrorCode::SlippageExceeded.into());
+        }
+
+        let referral_cut = pay_referral(user, &mut ctx.accounts.referrer_stats, &mut ctx.accounts.referral_vault, payer, &ctx.accounts.system_program, lamports)?;
+        let staking_deposit = collect_point_payment(payer, treasury, staking_treasury, &mut ctx.accounts.fee_vault, &mut ctx.accounts.season_state, &ctx.accounts.system_program, lamports - referral_cut)?;
+        distribute_yield(&mut ctx.accounts.yield_accumulator, ctx.accounts.total_won_points.points, staking_deposit)?;
+
+        ctx.accounts.game_stats.total_deposited += lamports;
+
+        // Add points to user
+        user.points += quantity as i64;
+        msg!("User {} paid {} lamports and received {} points.", payer.key(), lamports, quantity);
+        msg!("Buy Points: Yield Pool {}",  **staking_treasury.to_account_info().lamports.borrow());
+
+        Ok(())
+    }
+
+    pub fn claim_lamports(ctx: Context<ClaimLamports>, _bump: u8) -> Result<()> {
+        let accounts = ctx.accounts;
+
+        // Redeem every won point along with any yield credited from staking
+        let tokens = point_tokens(&accounts.points_mint, &accounts.user_points_account, &accounts.token_program)?;
+        let won_points = accounts.user.won_points.max(0) as u64;
+        redeem(&mut accounts.user, &mut accounts.total_won_points, &mut accounts.staking_treasury, &accounts.yield_accumulator, &mut accounts.game_stats, &accounts.payer, tokens, won_points, 0)?;
+
+        Ok(())
+    }
+
+    pub fn close_user(ctx: Context<CloseUser>, force: bool, min_lamports_out: u64) -> Result<()> {
+        let accounts = ctx.accounts;
+
+        // Won points only go away through redemption, so total_won_points stays in step
+        let won_points = accounts.user.won_points.max(0) as u64;
+        if won_points > 0 && !force {
+            return Err(ErrorCode::UserNotEmpty.into());
+        }
+        if won_points > 0 || accounts.user.pending_yield > 0 {
+            let tokens = point_tokens(&accounts.points_mint, &accounts.user_points_account, &accounts.token_program)?;
+            redeem(&mut accounts.user, &mut accounts.total_won_points, &mut accounts.staking_treasury, &accounts.yield_accumulator, &mut accounts.game_stats, &accounts.payer, tokens, won_points, min_lamports_out)?;
+        }
+
+        // Bought points have no redemption value, spend them before closing or forfeit them to the jackpot
+        let leftover_points = accounts.user.points.max(0);
+        if leftover_points > 0 {
+            if !force {
+                return Err(ErrorCode::UserNotEmpty.into());
+            }
+            accounts.jackpot.amount += leftover_points;
+            accounts.user.points = 0;
+            msg!("User {} forfeited {} points to the jackpot.", accounts.payer.key(), leftover_points);
+        }
+
+        msg!("User {} closed, rent returned.", accounts.payer.key());
+        Ok(())
+    }
+
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        let target = &ctx.accounts.target;
+        let payer = &ctx.accounts.payer;
+
+        let discriminator: [u8; 8] = target.try_borrow_data()?
+            .get(..8)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(ErrorCode::UnknownAccountLayout)?;
+        let (len, version) = ACCOUNT_LAYOUTS
+            .iter()
+            .find(|(layout_discriminator, _, _)| *layout_discriminator == discriminator)
+            .map(|(_, len, version)| (*len, *version))
+            .ok_or(ErrorCode::UnknownAccountLayout)?;
+
+        // Legacy accounts were hand-sized, some larger than their versioned LEN, so match the old size exactly
+        let old_len = target.data_len();
+        let old_version = OLDER_LAYOUTS
+            .iter()
+            .find(|(older_discriminator, older_len, _)| *older_discriminator == discriminator && *older_len == old_len)
+            .map(|(_, _, older_version)| *older_version)
+            .ok_or(ErrorCode::AlreadyMigrated)?;
+        // Never shrink, every legacy byte still has to fit behind the header
+        let len = len.max(old_len + 1);
+
+        let rent_top_up = Rent::get()?.minimum_balance(len).saturating_sub(target.lamports());
+        if rent_top_up > 0 {
+            let transfer_instruction = anchor_lang::solana_program::system_instruction::transfer(
+                &payer.key(),
+                &target.key(),
+                rent_top_up,
+            );
+            anchor_lang::solana_program::program::invoke(
+                &transfer_instruction,
+                &[
+                    payer.to_account_info(),
+                    target.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
+        }
+
+        if discriminator == PlayerList::DISCRIMINATOR {
+            migrate_player_list(target, old_version, len, version)?;
+        } else {
+            // Grow in place; versioned layouts only append fields, legacy ones slide past the new header byte.
+            // Appended fields start zeroed either way.
+            target.realloc(len, true)?;
+            let mut data = target.try_borrow_mut_data()?;
+            if old_version == 0 {
+                data.copy_within(8..old_len, 9);
+            }
+            data[8] = version;
+        }
+
+        // Legacy users predate the yield accumulator, their won points only earn from here on
+        if discriminator == User::DISCRIMINATOR && old_version == 0 {
+            let mut user = User::try_deserialize(&mut &target.try_borrow_data()?[..])?;
+            sync_reward_debt(&mut user, &ctx.accounts.yield_accumulator);
+            user.try_serialize(&mut &mut target.try_borrow_mut_data()?[..])?;
+        }
+
+        emit!(AccountMigrated {
+            account: target.key(),
+            old_len: old_len as u32,
+            new_len: len as u32,
+            version,
+        });
+        msg!("Account {} migrated from {} to {} bytes at version {}.", target.key(), old_len, len, version);
+        Ok(())
+    }
+
+    pub fn initialize_yield_accumulator(ctx: Context<InitializeYieldAccumulator>) -> Result<()> {
+        ctx.accounts.yield_accumulator.version = YieldAccumulator::VERSION;
+        let yield_accumulator = &mut ctx.accounts.yield_accumulator;
+        let staking_treasury = &ctx.accounts.staking_treasury;
+
+        // Yield already in the pool is handed out with the first deposit after this
+        if yield_accumulator.acc_reward_per_point == 0 && yield_accumulator.unallocated == 0 {
+            let rent_exemption = Rent::get()?.minimum_balance(staking_treasury.to_account_info().data_len());
+            yield_accumulator.unallocated = staking_treasury.to_account_info().lamports().saturating_sub(rent_exemption);
+            msg!("Yield accumulator initialized with {} unallocated lamports.", yield_accumulator.unallocated);
+        } else {
+            msg!("Yield accumulator already initialized {}", yield_accumulator.key());
+        }
+
+        Ok(())
+    }
+}
+
+// Power-up percentage based on how many slots have passed since the user's last win
+fn power_up_for(last_won_slot: u64, slot: u64) -> f64 {
+    let slots_since_last_win = slot - last_won_slot;
+    if last_won_slot == 0 {
+        0.1 // New user
+    } else if slots_since_last_win >= 600 {
+        0.75
+    } else if slots_since_last_win >= 300 {
+        0.5
+    } else if slots_since_last_win >= 100 {
+        0.25
+    } else {
+        0.1
+    }
+}
+
+fn power_up_bps(power_up: f64) -> u16 {
+    (power_up * 10_000.0).round() as u16
+}
+
+// Kept up to date by PlayerList::push, anyone can recheck it from the entries inside the window
+fn recent_players_sum(player_list: &PlayerList) -> u64 {
+    player_list.window_sum
+}
+
+// Profile counters for one play, a batch of rounds counts once towards the win streak
+fn record_play(user: &mut User, slot: u64, rounds: u64, won: bool) {
+    if user.first_play_slot == 0 {
+        user.first_play_slot = slot;
+    }
+    user.last_play_slot = slot;
+    user.rounds_played += rounds;
+    if won {
+        user.current_streak += 1;
+        user.best_streak = user.best_streak.max(user.current_streak);
+    } else {
+        user.current_streak = 0;
+    }
+    unlock_achievements(user);
+}
+
+// `lamports` is the whole prize after fees, vested part included
+fn record_user_win(user: &mut User, lamports: u64, power_up: f64, number: u64, superprime: bool) {
+    user.wins += 1;
+    if superprime {
+        user.superprimes += 1;
+    }
+    user.best_power_up_bps = user.best_power_up_bps.max(power_up_bps(power_up));
+    user.lamports_won += lamports;
+    // A fixed witness set, so the same twin always unlocks whatever the clock reads
+    let is_twin = |other: Option<u64>| other.is_some_and(is_prime_deterministic);
+    if is_twin(number.checked_sub(2)) || is_twin(number.checked_add(2)) {
+        user.achievements |= 1 << ACHIEVEMENT_TWIN_PRIME;
+    }
+    unlock_achievements(user);
+}
+
+// Achievements that follow from the profile counters, so players from before them unlock on their next play
+fn unlock_achievements(user: &mut User) {
+    let unlocked = [
+        (ACHIEVEMENT_FIRST_PRIME, user.wins > 0),
+        (ACHIEVEMENT_FIRST_SUPERPRIME, user.superprimes > 0),
+        (ACHIEVEMENT_WIN_STREAK, user.best_streak >= ACHIEVEMENT_STREAK),
+        (ACHIEVEMENT_CENTURY, user.rounds_played >= ACHIEVEMENT_ROUNDS),
+    ];
+    for (achievement, reached) in unlocked {
+        if reached {
+            user.achievements |= 1 << achievement;
+        }
+    }
+}
+
+fn record_game_rounds(game_stats: &mut GameStats, rounds: u64) {
+    game_stats.total_rounds += rounds;
+}
+
+fn record_game_win(game_stats: &mut GameStats, lamports: u64, superprime: bool) {
+    game_stats.total_wins += 1;
+    if superprime {
+        game_stats.total_superprimes += 1;
+    }
+    game_stats.total_paid_out += lamports;
+}
+
+// Epoch draws, season prizes and referral rewards leave the program without a round win
+fn record_game_payout(game_stats: &mut GameStats, lamports: u64) {
+    game_stats.total_paid_out += lamports;
+}
+
+// Candidates start from an odd base and step by an even stride, so no round is spent on an
+// even number. 7_918 = 2 * 37 * 107 shares no factor with 3 or 5 and ends in 8, so consecutive
+// candidates still cycle through every odd last digit.
+fn candidate_for_nonce(base_number: u64, nonce: u8) -> u64 {
+    (base_number | 1) + nonce as u64 * CANDIDATE_STRIDE
+}
+
+// Charge one round to the user and feed it into the jackpot
+fn spend_round_points(user: &mut User, total_won_points: &mut TotalWonPoints, jackpot: &mut Jackpot, cost: i64) -> Result<()> {
+    spend_won_points_first(user, total_won_points, user.points, cost)?;
+
+    // insert points in miner
+    user.points -= cost;
+    jackpot.amount += cost;
+    Ok(())
+}
+
+// Once a user's balance is all won points, rounds eat into their yield share
+fn spend_won_points_first(user: &mut User, total_won_points: &mut TotalWonPoints, balance: i64, cost: i64) -> Result<()> {
+    // deduct from user and total won points when user starts spending won points
+    if balance <= user.won_points {
+        user.won_points -= cost;
+        total_won_points.points = total_won_points.points.checked_sub(cost as u64).ok_or(ErrorCode::TotalWonPointsUnderflow)?;
+    }
+    Ok(())
+}
+
+fn mint_point_tokens<'info>(
+    points_mint: &InterfaceAccount<'info, Mint>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    mint_authority: &UncheckedAccount<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+    mint_authority_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    let signer_seeds: &[&[&[u8]]] = &[&[b"mint_authority", &[mint_authority_bump]]];
+    token_interface::mint_to(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            MintTo {
+                mint: points_mint.to_account_info(),
+                to: to.to_account_info(),
+                authority: mint_authority.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )
+}
+
+// Settle a win and credit the reward to the user's internal points
+#[allow(clippy::too_many_arguments)]
+fn award_prime<'info>(
+    user: &mut User,
+    jackpot: &mut Jackpot,
+    treasury: &mut Account<'info, Treasury>,
+    vesting: &mut Account<'info, Vesting>,
+    vesting_config: &VestingConfig,
+    fee_vault: &mut Account<'info, FeeVault>,
+    game_stats: &mut GameStats,
+    win_history: Option<&mut WinHistory>,
+    season: Option<&mut Season>,
+    payer: &Signer<'info>,
+    total_won_points: &mut Account<'info, TotalWonPoints>,
+    slot: u64,
+    number_to_test: u64,
+    power_up: f64,
+    superprime: bool,
+    cap: PayoutCap,
+) -> Result<()> {
+    let reward_points = settle_prime(user, jackpot, treasury, vesting, vesting_config, fee_vault, game_stats, win_history, season, payer, total_won_points, slot, number_to_test, power_up, superprime, cap)?;
+    user.points += reward_points;
+    Ok(())
+}
+
+// Everything a win does except crediting the reward, which is returned for the caller to pay out
+#[allow(clippy::too_many_arguments)]
+fn settle_prime<'info>(
+    user: &mut User,
+    jackpot: &mut Jackpot,
+    treasury: &mut Account<'info, Treasury>,
+    vesting: &mut Account<'info, Vesting>,
+    vesting_config: &VestingConfig,
+    fee_vault: &mut Account<'info, FeeVault>,
+    game_stats: &mut GameStats,
+    win_history: Option<&mut WinHistory>,
+    season: Option<&mut Season>,
+    payer: &Signer<'info>,
+    total_won_points: &mut Account<'info, TotalWonPoints>,
+    slot: u64,
+    number_to_test: u64,
+    power_up: f64,
+    superprime: bool,
+    cap: PayoutCap,
+) -> Result<i64> {
+    let reward_points = (jackpot.amount as f64 * power_up).round() as i64;
+
+    user.won_points += reward_points;
+    total_won_points.points += reward_points as u64;
+    user.last_won_slot = slot;
+    jackpot.winner = payer.key(); // Assign the payer's pubkey as the winner
+    msg!("Payer {} rewarded with {} points.", payer.key(), reward_points);
+
+    let lamports_won = transfer_from_treasury(treasury, vesting, vesting_config, fee_vault, payer, superprime, power_up, cap)?;
+    record_user_win(user, lamports_won, power_up, number_to_test, superprime);
+    record_game_win(game_stats, lamports_won, superprime);
+    // Table wins stay out of the global feed and the season, their pools are separate
+    if let Some(season) = season {
+        record_season_win(season, user, payer.key(), slot, reward_points as u64);
+    }
+    if let Some(win_history) = win_history {
+        win_history.push(WinEntry {
+            winner: payer.key(),
+            slot,
+            number: number_to_test,
+            power_up_bps: power_up_bps(power_up),
+            lamports: lamports_won,
+            points: reward_points as u64,
+            kind: if superprime { PRIME_KIND_SUPERPRIME } else { PRIME_KIND_PRIME },
+        });
+    }
+
+    msg!("User won with {} power-up", power_up);
+
+    // Send event
+    msg!("PrimeFound: slot={}, user_pubkey={}, power_up={}, number_to_test={}, reward_points={}",
+        slot,
+        payer.key(),
+        power_up,
+        number_to_test,
+        reward_points
+    );
+
+    // Adjust jackpot amount to ensure it doesn't go below zero
+    if jackpot.amount >= reward_points {
+        jackpot.amount -= reward_points;
+    } else {
+        jackpot.amount = 0;
+    }
+    Ok(reward_points)
+}
+
+// Take a settled reward back out of the yield-bearing won points, for wins paid from a pool of their own
+fn withhold_from_yield(user: &mut User, total_won_points: &mut TotalWonPoints, reward_points: i64) -> Result<()> {
+    user.won_points -= reward_points;
+    total_won_points.points = total_won_points.points.checked_sub(reward_points as u64).ok_or(ErrorCode::TotalWonPointsUnderflow)?;
+    Ok(())
+}
+
+fn update_guild_leaderboard(guild_leaderboard: &mut GuildLeaderboard, guild_id: u64, won_points: u64) {
+    guild_leaderboard.guilds.retain(|entry| entry.guild_id != guild_id);
+    guild_leaderboard.guilds.push(GuildEntry { guild_id, won_points });
+    guild_leaderboard.guilds.sort_by_key(|entry| std::cmp::Reverse(entry.won_points));
+    guild_leaderboard.guilds.truncate(MAX_GUILD_LEADERBOARD);
+}
+
+fn validate_suffix(suffix_digits: u8, suffix: u64) -> Result<()> {
+    match 10u64.checked_pow(suffix_digits as u32) {
+        Some(modulus) if suffix < modulus => Ok(()),
+        _ => Err(ErrorCode::InvalidSuffix.into()),
+    }
+}
+
+// Proof targets also can't overlap the main table's SuperPrime ending, even though proofs never pay as SuperPrimes
+fn validate_search_suffix(suffix_digits: u8, suffix: u64) -> Result<()> {
+    validate_suffix(suffix_digits, suffix)?;
+    let shared = 10u64.pow(suffix_digits.min(SUPERPRIME_DIGITS) as u32);
+    if suffix % shared == SUPERPRIME_SUFFIX % shared {
+        return Err(ErrorCode::InvalidSuffix.into());
+    }
+    Ok(())
+}
+
+// Look up a slot's hash in the SlotHashes sysvar without deserializing all 512 entries
+fn find_slot_hash(slot_hashes: &AccountInfo, slot: u64) -> Result<[u8; 32]> {
+    let data = slot_hashes.try_borrow_data()?;
+    let len = u64::from_le_bytes(data[..8].try_into().unwrap()) as usize;
+
+    // Entries are (slot: u64, hash: [u8; 32]) ordered newest first
+    for entry in data[8..].chunks_exact(40).take(len) {
+        let entry_slot = u64::from_le_bytes(entry[..8].try_into().unwrap());
+        if entry_slot == slot {
+            return Ok(entry[8..].try_into().unwrap());
+        }
+        if entry_slot < slot {
+            break;
+        }
+    }
+    Err(ErrorCode::SlotHashNotFound.into())
+}
+
+// Start a difficulty controller at 1, its first window opening at `slot`
+fn open_difficulty(difficulty: &mut Difficulty, target_win_interval: u64, retarget_interval: u64, slot: u64) -> Result<()> {
+    if target_win_interval == 0 || retarget_interval == 0 {
+        return Err(ErrorCode::InvalidDifficultyParams.into());
+    }
+    difficulty.value = 1;
+    difficulty.target_win_interval = target_win_interval;
+    difficulty.retarget_interval = retarget_interval;
+    difficulty.wins_since_retarget = 0;
+    difficulty.window_start_slot = slot;
+    Ok(())
+}
+
+// Count a win and retarget once `retarget_interval` wins have landed in the current window
+fn record_win(difficulty: &mut Difficulty, slot: u64) {
+    difficulty.wins_since_retarget += 1;
+    if difficulty.wins_since_retarget < difficulty.retarget_interval {
+        return;
+    }
+
+    let actual_slots = slot.saturating_sub(difficulty.window_start_slot);
+    let old_difficulty = difficulty.value;
+    difficulty.value = retarget_difficulty(old_difficulty, actual_slots, difficulty.target_win_interval, difficulty.wins_since_retarget);
+
+    msg!("Difficulty retargeted from {} to {} after {} wins in {} slots.", old_difficulty, difficulty.value, difficulty.wins_since_retarget, actual_slots);
+    emit!(DifficultyAdjusted {
+        slot,
+        old_difficulty,
+        new_difficulty: difficulty.value,
+        wins: difficulty.wins_since_retarget,
+        actual_slots,
+        expected_slots: difficulty.target_win_interval.saturating_mul(difficulty.wins_since_retarget),
+    });
+
+    difficulty.wins_since_retarget = 0;
+    difficulty.window_start_slot = slot;
+}
+
+fn open_epoch(epoch: &mut Epoch, epoch_id: u64, start_slot: u64, epoch_length: u64, carried_in: u64) {
+    epoch.epoch_id = epoch_id;
+    epoch.start_slot = start_slot;
+    epoch.end_slot = start_slot + epoch_length;
+    epoch.carried_in = carried_in;
+    epoch.pot = 0;
+    epoch.rolled_forward = 0;
+    epoch.rounds = 0;
+    epoch.winners = Vec::new();
+    epoch.settled = false;
+}
+
+// Rounds played after end_slot but before the crank runs don't count toward the draw
+fn record_epoch_rounds(epoch: &mut Epoch, slot: u64, rounds: u64) {
+    if slot < epoch.end_slot {
+        epoch.rounds += rounds;
+    }
+}
+
+fn record_epoch_win(epoch: &mut Epoch, winner: Pubkey, slot: u64) {
+    if slot >= epoch.end_slot {
+        return;
+    }
+    if let Some(entry) = epoch.winners.iter_mut().find(|w| w.winner == winner) {
+        entry.primes += 1;
+    } else if epoch.winners.len() < MAX_EPOCH_WINNERS {
+        epoch.winners.push(EpochWinner { winner, primes: 1, payout: 0 });
+    }
+}
+
+fn validate_season_config(pool_bps: u16, distribution: &[u16]) -> Result<()> {
+    let total_bps: u32 = distribution.iter().map(|&bps| bps as u32).sum();
+    if pool_bps > MAX_SEASON_POOL_BPS || distribution.is_empty() || distribution.len() > MAX_SEASON_STANDINGS || total_bps > 10_000 {
+        return Err(ErrorCode::InvalidSeasonParams.into());
+    }
+    Ok(())
+}
+
+fn open_season(season: &mut Season, season_id: u64, start_slot: u64, season_length: u64, carried_in: u64) {
+    season.season_id = season_id;
+    season.start_slot = start_slot;
+    season.end_slot = start_slot + season_length;
+    season.carried_in = carried_in;
+    season.pot = 0;
+    season.paid = 0;
+    season.rolled_forward = 0;
+    season.standings = Vec::new();
+    season.finalized = false;
+    season.late_standings = Vec::new();
+}
+
+// Adds the win to the player's season points, then re-ranks them in the season's top-K index.
+// Wins after end_slot count towards the next season and are buffered until this one is finalized.
+fn record_season_win(season: &mut Season, user: &mut User, player: Pubkey, slot: u64, won_points: u64) {
+    let (season_id, standings) = if slot >= season.end_slot {
+        (season.season_id + 1, &mut season.late_standings)
+    } else {
+        (season.season_id, &mut season.standings)
+    };
+    if user.season_id != season_id {
+        user.season_id = season_id;
+        user.season_points = 0;
+    }
+    user.season_points += won_points;
+    rank_season_player(standings, player, user.season_points);
+}
+
+// The player's total replaces their entry, so someone who fell out of the index re-enters with everything they earned
+fn rank_season_player(standings: &mut Vec<SeasonEntry>, player: Pubkey, season_points: u64) {
+    if let Some(entry) = standings.iter_mut().find(|entry| entry.player == player) {
+        entry.won_points = season_points;
+    } else {
+        standings.push(SeasonEntry { player, won_points: season_points, payout: 0 });
+    }
+    standings.sort_by_key(|entry| std::cmp::Reverse(entry.won_points));
+    standings.truncate(MAX_SEASON_STANDINGS);
+}
+
+fn validate_table_config(config: &TableConfig) -> Result<()> {
+    if config.entry_cost == 0 || config.base_power_up_bps > 10_000 {
+        return Err(ErrorCode::InvalidTableConfig.into());
+    }
+    let mut previous_min_slots = 0;
+    for tier in config.power_up_tiers.iter() {
+        if tier.bps > 10_000 || tier.min_slots < previous_min_slots {
+            return Err(ErrorCode::InvalidTableConfig.into());
+        }
+        previous_min_slots = tier.min_slots;
+    }
+    if config.superprime_digits > 0 {
+        validate_suffix(config.superprime_digits, config.superprime_suffix)?;
+    }
+    Ok(())
+}
+
+// Same shape as power_up_for, with the thresholds and percentages taken from the table
+fn table_power_up(config: &TableConfig, last_won_slot: u64, slot: u64) -> f64 {
+    let slots_since_last_win = slot - last_won_slot;
+    let mut bps = config.base_power_up_bps;
+    if last_won_slot != 0 {
+        for tier in config.power_up_tiers.iter() {
+            if slots_since_last_win >= tier.min_slots {
+                bps = tier.bps;
+            }
+        }
+    }
+    bps as f64 / 10_000.0
+}
+
+// Split a point purchase 80/20 between the treasury and the yield pool
+#[allow(clippy::too_many_arguments)]
+fn collect_point_payment<'info>(
+    payer: &Signer<'info>,
+    treasury: &Account<'info, Treasury>,
+    staking_treasury: &Account<'info, StakingTreasury>,
+    fee_vault: &mut Account<'info, FeeVault>,
+    season_state: &mut Account<'info, SeasonState>,
+    system_program: &Program<'info, System>,
+    lamports: u64,
+) -> Result<u64> {
+    // The protocol fee and the season prize slice come off the top, the rest is split as before
+    let fee = lamports * fee_vault.purchase_fee_bps as u64 / 10_000;
+    let season_share = (lamports - fee) * season_state.pool_bps as u64 / 10_000;
+    let treasury_share = (lamports - fee - season_share) * TREASURY_BPS / 10_000;
+    let staking_share = lamports - fee - season_share - treasury_share;
+
+    if fee > 0 {
+        let fee_transfer_instruction = anchor_lang::solana_program::system_instruction::transfer(
+            &payer.key(),
+            &fee_vault.key(),
+            fee,
+        );
+        anchor_lang::solana_program::program::invoke(
+            &fee_transfer_instruction,
+            &[
+                payer.to_account_info(),
+                fee_vault.to_account_info(),
+                system_program.to_account_info(),
+            ],
+        )?;
+        record_fee(fee_vault, payer.key(), fee, false);
+    }
+
+    if season_share > 0 {
+        let season_transfer_instruction = anchor_lang::solana_program::system_instruction::transfer(
+            &payer.key(),
+            &season_state.key(),
+            season_share,
+        );
+        anchor_lang::solana_program::program::invoke(
+            &season_transfer_instruction,
+            &[
+                payer.to_account_info(),
+                season_state.to_account_info(),
+                system_program.to_account_info(),
+            ],
+        )?;
+        season_state.pool += season_share;
+    }
+
+    // Transfer 80% to the treasury using the system program
+    let transfer_instruction = anchor_lang::solana_program::system_instruction::transfer(
+        &payer.key(),
+        &treasury.key(),
+        treasury_share,
+    );
+    anchor_lang::solana_program::program::invoke(
+        &transfer_instruction,
+        &[
+            payer.to_account_info(),
+            treasury.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // Transfer the remaining 20% to the staking treasury
+    let staking_transfer_instruction = anchor_lang::solana_program::system_instruction::transfer(
+        &payer.key(),
+        &staking_treasury.key(),
+        staking_share,
+    );
+    anchor_lang::solana_program::program::invoke(
+        &staking_transfer_instruction,
+        &[
+            payer.to_account_info(),
+            staking_treasury.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    Ok(staking_share)
+}
+
+// Price `quantity` points at the deepest volume tier reached, rounding up so no purchase is free
+fn quote_points(pricing: &PricingConfig, quantity: u64) -> Result<u64> {
+    quote_points_at(pricing, pricing.lamports_per_1000, quantity)
+}
+
+// Same bounds and tiers at another base price, SPL mints are priced in their own units
+fn quote_points_at(pricing: &PricingConfig, price_per_1000: u64, quantity: u64) -> Result<u64> {
+    if quantity < pricing.min_quantity || quantity > pricing.max_quantity {
+        return Err(ErrorCode::QuantityOutOfRange.into());
+    }
+
+    let discount_bps = pricing
+        .volume_tiers
+        .iter()
+        .filter(|tier| quantity >= tier.min_quantity)
+        .map(|tier| tier.discount_bps)
+        .max()
+        .unwrap_or(0);
+
+    let numerator = quantity as u128 * price_per_1000 as u128 * (10_000 - discount_bps as u128);
+    let denominator = 1000u128 * 10_000;
+    Ok(numerator.div_ceil(denominator) as u64)
+}
+
+fn validate_pricing(params: &PricingParams) -> Result<()> {
+    if params.lamports_per_1000 == 0
+        || params.min_quantity == 0
+        || params.min_quantity > params.max_quantity
+        || params.volume_tiers.len() > MAX_VOLUME_TIERS
+        || params.volume_tiers.iter().any(|tier| tier.discount_bps >= 10_000)
+    {
+        return Err(ErrorCode::InvalidPricing.into());
+    }
+    Ok(())
+}
+
+// Credit the user with yield from deposits since their reward debt was last synced
+fn accrue_yield(user: &mut User, yield_accumulator: &YieldAccumulator) {
+    let accrued = user.won_points.max(0) as u128 * yield_accumulator.acc_reward_per_point / ACC_PRECISION;
+    user.pending_yield += accrued.saturating_sub(user.reward_debt) as u64;
+    user.reward_debt = accrued;
+}
+
+// Call after won_points changes so the new balance only earns from future deposits
+fn sync_reward_debt(user: &mut User, yield_accumulator: &YieldAccumulator) {
+    user.reward_debt = user.won_points.max(0) as u128 * yield_accumulator.acc_reward_per_point / ACC_PRECISION;
+}
+
+// Spread a yield pool deposit over every won point outstanding right now, staked ones at their boosted weight
+fn distribute_yield(yield_accumulator: &mut YieldAccumulator, total_won_points: u64, deposit: u64) -> Result<()> {
+    let total_shares = total_won_points as u128 + yield_accumulator.total_stake_weight as u128;
+    accumulate(&mut yield_accumulator.acc_reward_per_point, &mut yield_accumulator.unallocated, total_shares, deposit)
+}
+
+// Add a deposit, plus anything carried over, to a reward-per-share accumulator
+fn accumulate(acc_reward_per_point: &mut u128, unallocated: &mut u64, total_shares: u128, deposit: u64) -> Result<()> {
+    let amount = deposit.checked_add(*unallocated).ok_or(ErrorCode::YieldOverflow)?;
+    if total_shares == 0 {
+        // Nobody holds won points yet, keep it for the next deposit
+        *unallocated = amount;
+        return Ok(());
+    }
+    // The accumulator only ever grows, wrapping would hand every holder a bogus claim
+    let increment = (amount as u128).checked_mul(ACC_PRECISION).ok_or(ErrorCode::YieldOverflow)? / total_shares;
+    *acc_reward_per_point = acc_reward_per_point.checked_add(increment).ok_or(ErrorCode::YieldOverflow)?;
+    *unallocated = 0;
+    Ok(())
+}
+
+// Same bookkeeping as accrue_yield, for won points put behind a mint
+fn accrue_spl_yield(position: &mut SplYieldPosition, pool: &SplYieldPool) {
+    let accrued = position.amount as u128 * pool.acc_reward_per_point / ACC_PRECISION;
+    position.pending_tokens += accrued.saturating_sub(position.reward_debt) as u64;
+    position.reward_debt = accrued;
+}
+
+// Accrued yield backs the redemption price: a partial redemption takes the matching share, a full one takes it all
+fn quote_redemption_lamports(user: &User, amount: u64) -> Result<u64> {
+    redemption_share(user.pending_yield, user.won_points.max(0) as u64, amount)
+}
+
+fn redemption_share(pending: u64, held: u64, amount: u64) -> Result<u64> {
+    if amount > held {
+        return Err(ErrorCode::InvalidRedemption.into());
+    }
+    if amount == held {
+        return Ok(pending);
+    }
+    Ok((pending as u128 * amount as u128 / held as u128) as u64)
+}
+
+// POINTS token accounts a token-mode player passes so their token balance backs won points too
+struct PointTokens<'a, 'info> {
+    points_mint: &'a InterfaceAccount<'info, Mint>,
+    user_points_account: &'a InterfaceAccount<'info, TokenAccount>,
+    token_program: &'a Interface<'info, TokenInterface>,
+}
+
+// The optional token accounts of a redemption come as a set or not at all
+fn point_tokens<'a, 'info>(
+    points_mint: &'a Option<Box<InterfaceAccount<'info, Mint>>>,
+    user_points_account: &'a Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    token_program: &'a Option<Interface<'info, TokenInterface>>,
+) -> Result<Option<PointTokens<'a, 'info>>> {
+    match (points_mint, user_points_account, token_program) {
+        (Some(points_mint), Some(user_points_account), Some(token_program)) => Ok(Some(PointTokens { points_mint, user_points_account, token_program })),
+        (None, None, None) => Ok(None),
+        _ => Err(ErrorCode::IncompletePointTokenAccounts.into()),
+    }
+}
+
+// The only path that turns won points into lamports. Won points are backed by User.points plus,
+// in token mode, the POINTS token balance; the redeemed amount comes off User.points first and
+// the rest is burned, so the same balance can't be redeemed and then spent again.
+#[allow(clippy::too_many_arguments)]
+fn redeem<'info>(
+    user: &mut User,
+    total_won_points: &mut TotalWonPoints,
+    staking_treasury: &mut Account<'info, StakingTreasury>,
+    yield_accumulator: &YieldAccumulator,
+    game_stats: &mut GameStats,
+    payer: &Signer<'info>,
+    tokens: Option<PointTokens<'_, 'info>>,
+    amount: u64,
+    min_lamports_out: u64,
+) -> Result<u64> {
+    // Won points are always part of the spendable balance
+    let token_balance = tokens.as_ref().map_or(0, |tokens| tokens.user_points_account.amount);
+    if user.won_points < 0 || user.won_points as i128 > user.points as i128 + token_balance as i128 {
+        return Err(ErrorCode::PointsInvariantViolated.into());
+    }
+
+    accrue_yield(user, yield_accumulator);
+    let lamports_out = quote_redemption_lamports(user, amount)?;
+    if lamports_out < min_lamports_out {
+        return Err(ErrorCode::SlippageExceeded.into());
+    }
+
+    let from_points = amount.min(user.points.max(0) as u64);
+    let from_tokens = amount - from_points;
+    if let Some(tokens) = tokens.filter(|_| from_tokens > 0) {
+        token_interface::burn(
+            CpiContext::new(
+                tokens.token_program.to_account_info(),
+                Burn {
+                    mint: tokens.points_mint.to_account_info(),
+                    from: tokens.user_points_account.to_account_info(),
+                    authority: payer.to_account_info(),
+                },
+            ),
+            from_tokens,
+        )?;
+    }
+
+    transfer_from_staking_treasury(staking_treasury, payer, lamports_out)?;
+    user.pending_yield -= lamports_out;
+    user.won_points -= amount as i64;
+    user.points -= from_points as i64;
+    total_won_points.points = total_won_points.points.checked_sub(amount).ok_or(ErrorCode::TotalWonPointsUnderflow)?;
+    sync_reward_debt(user, yield_accumulator);
+
+    let current_slot = Clock::get()?.slot;
+    user.last_claimed_slot = current_slot;
+    user.last_claimed_lamports = lamports_out;
+    user.lamports_claimed += lamports_out;
+    game_stats.total_yield_claimed += lamports_out;
+
+    if user.won_points < 0 || user.won_points as i128 > user.points as i128 + (token_balance - from_tokens) as i128 {
+        return Err(ErrorCode::PointsInvariantViolated.into());
+    }
+
+    emit!(WonPointsRedeemed {
+        user: payer.key(),
+        amount,
+        lamports_out,
+        slot: current_slot,
+    });
+    msg!("Redeemed {} won points ({} as burned POINTS tokens) for {} lamports, {} won points left", amount, from_tokens, lamports_out, user.won_points);
+    Ok(lamports_out)
+}
+
+// Multiplier of the longest tier a lock qualifies for
+fn stake_multiplier_bps(lock_slots: u64) -> Option<u64> {
+    STAKE_TIERS
+        .iter()
+        .rev()
+        .find(|(min_lock, _)| lock_slots >= *min_lock)
+        .map(|(_, multiplier_bps)| *multiplier_bps)
+}
+
+// Same bookkeeping as accrue_yield, for a position's boosted weight
+fn accrue_stake_yield(position: &mut StakePosition, yield_accumulator: &YieldAccumulator) -> u64 {
+    let accrued = position.weight as u128 * yield_accumulator.acc_reward_per_point / ACC_PRECISION;
+    let earned = accrued.saturating_sub(position.reward_debt) as u64;
+    position.reward_debt = accrued;
+    earned
+}
+
+fn transfer_from_staking_treasury(
+    staking_treasury: &mut Account<StakingTreasury>,
+    payer: &Signer,
+    transfer_amount: u64,
+) -> Result<u64> {
+    let staking_treasury_balance = **staking_treasury.to_account_info().lamports.borrow();
+    let rent_exemption = Rent::get()?.minimum_balance(staking_treasury.to_account_info().data_len());
+
+    // Ensure the transfer amount doesn't exceed the balance minus rent exemption
+    if transfer_amount > staking_treasury_balance.checked_sub(rent_exemption).ok_or(ProgramError::InsufficientFunds)? {
+        return Err(ProgramError::InsufficientFunds.into());
+    }
+
+    **staking_treasury.to_account_info().lamports.borrow_mut() -= transfer_amount;
+    **payer.to_account_info().lamports.borrow_mut() += transfer_amount;
+
+    msg!("Transferred {} lamports from staking treasury {} to user {}", transfer_amount, staking_treasury.key(), payer.key());
+    Ok(transfer_amount)
+}
+
+// How much of the treasury a win may take: `bps` of its usual payout, never dipping below `floor` lamports above rent
+#[derive(Clone, Copy)]
+struct PayoutCap {
+    bps: u16,
+    floor: u64,
+}
+
+impl PayoutCap {
+    const FULL: PayoutCap = PayoutCap { bps: 10_000, floor: 0 };
+}
+
+// Calculate the amount to transfer based on the prime number ending, power-up and cap
+fn win_payout(above_rent: u64, superprime: bool, power_up: f64, cap: PayoutCap) -> u64 {
+    let available = above_rent.saturating_sub(cap.floor);
+    let amount = if superprime {
+        available
+    } else {
+        (available as f64 * power_up) as u64
+    };
+    (amount as u128 * cap.bps as u128 / 10_000) as u64
+}
+
+#[allow(clippy::too_many_arguments)]
+fn transfer_from_treasury(
+    treasury: &mut Account<Treasury>,
+    vesting: &mut Account<Vesting>,
+    vesting_config: &VestingConfig,
+    fee_vault: &mut Account<FeeVault>,
+    payer: &Signer,
+    superprime: bool,
+    power_up: f64,
+    cap: PayoutCap,
+) -> Result<u64> {
+    let treasury_balance = **treasury.to_account_info().lamports.borrow();
+    let rent_exemption = Rent::get()?.minimum_balance(treasury.to_account_info().data_len());
+    let payer_pubkey = payer.key();
+    let above_rent = treasury_balance.checked_sub(rent_exemption).ok_or(ProgramError::InsufficientFunds)?;
+    let transfer_amount = win_payout(above_rent, superprime, power_up, cap);
+
+    // Protocol fee on the win goes straight from the treasury to the fee vault
+    let fee = transfer_amount * fee_vault.win_fee_bps as u64 / 10_000;
+    if fee > 0 {
+        **treasury.to_account_info().lamports.borrow_mut() -= fee;
+        **fee_vault.to_account_info().lamports.borrow_mut() += fee;
+        record_fee(fee_vault, payer_pubkey, fee, true);
+    }
+    let transfer_amount = transfer_amount - fee;
+
+    // Large wins pay a slice now and lock the rest in the winner's vesting account
+    let immediate_amount = if transfer_amount > vesting_config.threshold_lamports {
+        (transfer_amount as u128 * vesting_config.immediate_bps as u128 / 10_000) as u64
+    } else {
+        transfer_amount
+    };
+    let vested_amount = transfer_amount - immediate_amount;
+
+    **treasury.to_account_info().lamports.borrow_mut() -= transfer_amount;
+    **payer.to_account_info().lamports.borrow_mut() += immediate_amount;
+    if vested_amount > 0 {
+        **vesting.to_account_info().lamports.borrow_mut() += vested_amount;
+        add_to_vesting(vesting, vested_amount, Clock::get()?.slot, vesting_config.vesting_slots);
+    }
+
+    msg!("Transferred {} lamports from treasury {} to user {}", transfer_amount, treasury.key(), payer.key());
+    msg!("Winner: User: {} Lamports: {} Vested: {} Power-up: {}", payer_pubkey, immediate_amount, vested_amount, power_up);
+    Ok(transfer_amount)
+}
+
+fn validate_vesting_config(immediate_bps: u16, vesting_slots: u64) -> Result<()> {
+    if immediate_bps > 10_000 || vesting_slots == 0 {
+        return Err(ErrorCode::InvalidVestingConfig.into());
+    }
+    Ok(())
+}
+
+// Bind the referrer on the first referred purchase, returns the referrer's share of it in bps
+fn bind_referrer<'info>(
+    user: &mut User,
+    referrer_stats: &mut Option<Box<Account<'info, ReferrerStats>>>,
+    referral_vault: &Option<Box<Account<'info, ReferralVault>>>,
+    payer: &Signer<'info>,
+) -> Result<u16> {
+    let Some(stats) = referrer_stats.as_mut() else {
+        // Once bound, the referrer has to be passed on every purchase
+        if user.referrer != Pubkey::default() {
+            return Err(ErrorCode::ReferrerMismatch.into());
+        }
+        return Ok(0);
+    };
+    let referral_vault = referral_vault.as_ref().ok_or(ErrorCode::ReferrerMismatch)?;
+
+    if user.referrer == Pubkey::default() {
+        if stats.referrer == payer.key() {
+            return Err(ErrorCode::InvalidReferrer.into());
+        }
+        user.referrer = stats.referrer;
+        stats.referred_users += 1;
+        msg!("User {} referred by {}", payer.key(), stats.referrer);
+    } else if user.referrer != stats.referrer {
+        return Err(ErrorCode::ReferrerMismatch.into());
+    }
+
+    stats.referred_purchases += 1;
+    Ok(referral_vault.referral_bps)
+}
+
+// Move the referrer's cut of a SOL purchase into the referral vault
+fn pay_referral<'info>(
+    user: &mut User,
+    referrer_stats: &mut Option<Box<Account<'info, ReferrerStats>>>,
+    referral_vault: &mut Option<Box<Account<'info, ReferralVault>>>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    lamports: u64,
+) -> Result<u64> {
+    let referral_bps = bind_referrer(user, referrer_stats, referral_vault, payer)?;
+    let (Some(stats), Some(referral_vault)) = (referrer_stats.as_mut(), referral_vault.as_mut()) else {
+        return Ok(0);
+    };
+
+    let reward = lamports * referral_bps as u64 / 10_000;
+    if reward > 0 {
+        let referral_transfer_instruction = anchor_lang::solana_program::system_instruction::transfer(
+            &payer.key(),
+            &referral_vault.key(),
+            reward,
+        );
+        anchor_lang::solana_program::program::invoke(
+            &referral_transfer_instruction,
+            &[
+                payer.to_account_info(),
+                referral_vault.to_account_info(),
+                system_program.to_account_info(),
+            ],
+        )?;
+    }
+
+    stats.referred_volume += lamports;
+    stats.earned += reward;
+    referral_vault.total_earned += reward;
+    emit!(ReferralRewarded {
+        referrer: stats.referrer,
+        buyer: payer.key(),
+        purchase_lamports: lamports,
+        reward,
+    });
+    Ok(reward)
+}
+
+fn transfer_spl_payment<'info>(
+    from: &InterfaceAccount<'info, TokenAccount>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    payer: &Signer<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    token_interface::transfer_checked(
+        CpiContext::new(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: from.to_account_info(),
+                mint: mint.to_account_info(),
+                to: to.to_account_info(),
+                authority: payer.to_account_info(),
+            },
+        ),
+        amount,
+        mint.decimals,
+    )
+}
+
+fn record_fee(fee_vault: &mut FeeVault, payer: Pubkey, fee: u64, from_win: bool) {
+    if from_win {
+        fee_vault.collected_from_wins += fee;
+    } else {
+        fee_vault.collected_from_purchases += fee;
+    }
+    emit!(FeeCollected {
+        payer,
+        amount: fee,
+        from_win,
+    });
+}
+
+fn validate_fee_bps(purchase_fee_bps: u16, win_fee_bps: u16) -> Result<()> {
+    if purchase_fee_bps > MAX_FEE_BPS || win_fee_bps > MAX_FEE_BPS {
+        return Err(ErrorCode::FeeTooHigh.into());
+    }
+    Ok(())
+}
+
+// Stamp a freshly created vesting account, rounds create it on demand
+fn open_vesting(vesting: &mut Vesting, beneficiary: Pubkey) {
+    if vesting.beneficiary == Pubkey::default() {
+        vesting.version = Vesting::VERSION;
+        vesting.beneficiary = beneficiary;
+    }
+}
+
+// Portion of the current schedule released by `slot`
+fn vested_by(vesting: &Vesting, slot: u64) -> u64 {
+    if slot >= vesting.end_slot {
+        return vesting.amount;
+    }
+    let elapsed = slot.saturating_sub(vesting.start_slot) as u128;
+    let duration = (vesting.end_slot - vesting.start_slot) as u128;
+    (vesting.amount as u128 * elapsed / duration) as u64
+}
+
+// Restart the schedule with the still-locked remainder plus the new amount, keeping what already released
+fn add_to_vesting(vesting: &mut Vesting, amount: u64, slot: u64, vesting_slots: u64) {
+    let released = vested_by(vesting, slot);
+    vesting.unclaimed += released - vesting.claimed;
+    vesting.amount = vesting.amount - released + amount;
+    vesting.claimed = 0;
+    vesting.start_slot = slot;
+    vesting.end_slot = slot + vesting_slots;
+}
+
+// Replays a Borsh Vec<Pubkey> player list into the ring buffer, oldest first.
+// Those lists never recorded when a player played, so their entries carry slot 0.
+fn migrate_player_list(target: &AccountInfo, old_version: u8, len: usize, version: u8) -> Result<()> {
+    // The unversioned list starts its Vec right after the discriminator, version 1 after the header byte
+    let players_offset = if old_version == 0 { 8 } else { 9 };
+    let players = Vec::<Pubkey>::deserialize(&mut &target.try_borrow_data()?[players_offset..])?;
+
+    target.realloc(len, true)?;
+    let mut data = target.try_borrow_mut_data()?;
+    data[8..].fill(0);
+    let player_list: &mut PlayerList = bytemuck::from_bytes_mut(&mut data[8..len]);
+    player_list.version = version;
+    player_list.window = DEFAULT_PLAYER_WINDOW;
+    for player in players {
+        player_list.push(player, 0);
+    }
+    Ok(())
+}
+/*
+fn update_leaderboard(leaderboard: &mut Account<Leaderboard>, user: Pubkey, points: i64) {
+    // Remove the user if they are already in the leaderboard
+    leaderboard.users.retain(|entry| entry.user != user);
+
+    // Add the user back with updated points
+    leaderboard.users.push(UserEntry { user, points });
+
+    // Sort the leaderboard by points in descending order and keep only the top 100
+    leaderboard.users.sort_by(|a, b| b.points.cmp(&a.points));
+    leaderboard.users.truncate(100);
+
+    // Log the specific user and their points
+    msg!("Leaderboard: User: {}, Points: {}", user.to_string(), points);
+}
+*/
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init_if_needed, payer = payer, space = Jackpot::LEN, seeds = [b"jackpot"], bump)]
+    pub jackpot: Box<Account<'info, Jackpot>>,
+    #[account(init_if_needed, payer = payer, space = Treasury::LEN, seeds = [b"treasury"], bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+    #[account(init_if_needed, payer = payer, space = PlayerList::LEN, seeds = [b"player_list"], bump)]
+    pub player_list: AccountLoader<'info, PlayerList>,
+    // Only the program's upgrade authority may become the player list admin, migrated lists included
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::PrimeSlotChecker>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(payer.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Box<Account<'info, ProgramData>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Works on the global list and on table lists alike, whoever is recorded as the list's admin
+#[derive(Accounts)]
+pub struct SetPlayerWindow<'info> {
+    #[account(mut, has_one = admin @ ErrorCode::Unauthorized)]
+    pub player_list: AccountLoader<'info, PlayerList>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeGameStats<'info> {
+    #[account(init_if_needed, payer = payer, space = GameStats::LEN, seeds = [b"game_stats"], bump)]
+    pub game_stats: Box<Account<'info, GameStats>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GetStats<'info> {
+    #[account(seeds = [b"game_stats"], bump)]
+    pub game_stats: Box<Account<'info, GameStats>>,
+    #[account(seeds = [b"treasury"], bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+    #[account(seeds = [b"staking_treasury"], bump)]
+    pub staking_treasury: Box<Account<'info, StakingTreasury>>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeWinHistory<'info> {
+    #[account(init_if_needed, payer = payer, space = WinHistory::LEN, seeds = [b"win_history"], bump)]
+    pub win_history: AccountLoader<'info, WinHistory>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeTotalWonPoints<'info> {
+    #[account(init_if_needed, payer = payer, space = TotalWonPoints::LEN, seeds = [b"total_won_points"], bump)]
+    pub total_won_points: Box<Account<'info, TotalWonPoints>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeStakingTreasury<'info> {
+    #[account(init_if_needed, payer = payer, space = StakingTreasury::LEN, seeds = [b"staking_treasury"], bump)]
+    pub staking_treasury: Box<Account<'info, StakingTreasury>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeLeaderboard<'info> {
+    #[account(init_if_needed, payer = payer, space = Leaderboard::LEN, seeds = [b"leaderboard"], bump)]
+    pub leaderboard: Box<Account<'info, Leaderboard>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeUser<'info> {
+    #[account(init_if_needed, payer = payer, space = User::LEN, seeds = [b"user", payer.key().as_ref()], bump)]
+    pub user: Box<Account<'info, User>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeDifficulty<'info> {
+    #[account(init_if_needed, payer = payer, space = Difficulty::LEN, seeds = [b"difficulty"], bump)]
+    pub difficulty: Box<Account<'info, Difficulty>>,
+    // Only the program's upgrade authority may set the retarget parameters
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::PrimeSlotChecker>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(payer.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Box<Account<'info, ProgramData>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeEpochs<'info> {
+    #[account(init, payer = payer, space = EpochState::LEN, seeds = [b"epoch_state"], bump)]
+    pub epoch_state: Box<Account<'info, EpochState>>,
+    #[account(init, payer = payer, space = Epoch::LEN, seeds = [b"epoch", 0u64.to_le_bytes().as_ref()], bump)]
+    pub epoch: Box<Account<'info, Epoch>>,
+    // Only the program's upgrade authority may decide how much of the treasury each epoch draws
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::PrimeSlotChecker>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(payer.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Box<Account<'info, ProgramData>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseEpoch<'info> {
+    #[account(mut, seeds = [b"epoch_state"], bump)]
+    pub epoch_state: Box<Account<'info, EpochState>>,
+    #[account(mut, seeds = [b"epoch", epoch_state.current_epoch.to_le_bytes().as_ref()], bump)]
+    pub epoch: Box<Account<'info, Epoch>>,
+    #[account(init, payer = payer, space = Epoch::LEN, seeds = [b"epoch", (epoch_state.current_epoch + 1).to_le_bytes().as_ref()], bump)]
+    pub next_epoch: Box<Account<'info, Epoch>>,
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+    #[account(mut, seeds = [b"game_stats"], bump)]
+    pub game_stats: Box<Account<'info, GameStats>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeSeasons<'info> {
+    #[account(init, payer = payer, space = SeasonState::LEN, seeds = [b"season_state"], bump)]
+    pub season_state: Box<Account<'info, SeasonState>>,
+    #[account(init, payer = payer, space = Season::LEN, seeds = [b"season", 0u64.to_le_bytes().as_ref()], bump)]
+    pub season: Box<Account<'info, Season>>,
+    // Only the program's upgrade authority may schedule seasons and become their admin
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::PrimeSlotChecker>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(payer.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Box<Account<'info, ProgramData>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetSeasonConfig<'info> {
+    #[account(mut, seeds = [b"season_state"], bump, has_one = admin @ ErrorCode::Unauthorized)]
+    pub season_state: Box<Account<'info, SeasonState>>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeSeason<'info> {
+    #[account(mut, seeds = [b"season_state"], bump)]
+    pub season_state: Box<Account<'info, SeasonState>>,
+    #[account(mut, seeds = [b"season", season_state.current_season.to_le_bytes().as_ref()], bump)]
+    pub season: Box<Account<'info, Season>>,
+    #[account(init, payer = payer, space = Season::LEN, seeds = [b"season", (season_state.current_season + 1).to_le_bytes().as_ref()], bump)]
+    pub next_season: Box<Account<'info, Season>>,
+    #[account(mut, seeds = [b"game_stats"], bump)]
+    pub game_stats: Box<Account<'info, GameStats>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CheckSlot<'info> {
+    #[account(mut, seeds = [b"user", payer.key().as_ref()], bump)]
+    pub user: Box<Account<'info, User>>,
+    #[account(mut, seeds = [b"jackpot"], bump)]
+    pub jackpot: Box<Account<'info, Jackpot>>,
+    #[account(mut, seeds = [b"total_won_points"], bump)]
+    pub total_won_points: Box<Account<'info, TotalWonPoints>>,
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+    #[account(mut, seeds = [b"player_list"], bump)]
+    pub player_list: AccountLoader<'info, PlayerList>,
+    #[account(mut, seeds = [b"leaderboard"], bump)]
+    pub leaderboard: Box<Account<'info, Leaderboard>>,
+    #[account(mut, seeds = [b"staking_treasury"], bump)]
+    pub staking_treasury: Box<Account<'info, StakingTreasury>>,
+    #[account(mut, seeds = [b"difficulty"], bump)]
+    pub difficulty: Box<Account<'info, Difficulty>>,
+    #[account(seeds = [b"epoch_state"], bump)]
+    pub epoch_state: Box<Account<'info, EpochState>>,
+    #[account(mut, seeds = [b"epoch", epoch_state.current_epoch.to_le_bytes().as_ref()], bump)]
+    pub epoch: Box<Account<'info, Epoch>>,
+    #[account(seeds = [b"yield_accumulator"], bump)]
+    pub yield_accumulator: Box<Account<'info, YieldAccumulator>>,
+    #[account(seeds = [b"vesting_config"], bump)]
+    pub vesting_config: Box<Account<'info, VestingConfig>>,
+    // Created on the player's first round so a large win always has somewhere to vest
+    #[account(init_if_needed, payer = payer, space = Vesting::LEN, seeds = [b"vesting", payer.key().as_ref()], bump)]
+    pub vesting: Box<Account<'info, Vesting>>,
+    #[account(mut, seeds = [b"fee_vault"], bump)]
+    pub fee_vault: Box<Account<'info, FeeVault>>,
+    #[account(mut, seeds = [b"game_stats"], bump)]
+    pub game_stats: Box<Account<'info, GameStats>>,
+    #[account(mut, seeds = [b"win_history"], bump)]
+    pub win_history: AccountLoader<'info, WinHistory>,
+    #[account(seeds = [b"season_state"], bump)]
+    pub season_state: Box<Account<'info, SeasonState>>,
+    #[account(mut, seeds = [b"season", season_state.current_season.to_le_bytes().as_ref()], bump)]
+    pub season: Box<Account<'info, Season>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePricingConfig<'info> {
+    #[account(init_if_needed, payer = payer, space = PricingConfig::LEN, seeds = [b"pricing_config"], bump)]
+    pub pricing_config: Box<Account<'info, PricingConfig>>,
+    // Only the program's upgrade authority may set the point price and become the pricing admin
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::PrimeSlotChecker>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(payer.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Box<Account<'info, ProgramData>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPricing<'info> {
+    #[account(mut, seeds = [b"pricing_config"], bump, has_one = admin @ ErrorCode::Unauthorized)]
+    pub pricing_config: Box<Account<'info, PricingConfig>>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PayForPoints<'info> {
+    #[account(mut, seeds = [b"user", payer.key().as_ref()], bump)]
+    pub user: Box<Account<'info, User>>,
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+    #[account(mut, seeds = [b"staking_treasury"], bump)]
+    pub staking_treasury: Box<Account<'info, StakingTreasury>>,
+    #[account(seeds = [b"pricing_config"], bump)]
+    pub pricing_config: Box<Account<'info, PricingConfig>>,
+    #[account(seeds = [b"total_won_points"], bump)]
+    pub total_won_points: Box<Account<'info, TotalWonPoints>>,
+    #[account(mut, seeds = [b"yield_accumulator"], bump)]
+    pub yield_accumulator: Box<Account<'info, YieldAccumulator>>,
+    #[account(mut, seeds = [b"fee_vault"], bump)]
+    pub fee_vault: Box<Account<'info, FeeVault>>,
+    #[account(mut, seeds = [b"game_stats"], bump)]
+    pub game_stats: Box<Account<'info, GameStats>>,
+    #[account(mut, seeds = [b"season_state"], bump)]
+    pub season_state: Box<Account<'info, SeasonState>>,
+    // Needed on every purchase once the user has a referrer
+    #[account(mut)]
+    pub referrer_stats: Option<Box<Account<'info, ReferrerStats>>>,
+    #[account(mut, seeds = [b"referral_vault"], bump)]
+    pub referral_vault: Option<Box<Account<'info, ReferralVault>>>,
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeYieldAccumulator<'info> {
+    #[account(init_if_needed, payer = payer, space = YieldAccumulator::LEN, seeds = [b"yield_accumulator"], bump)]
+    pub yield_accumulator: Box<Account<'info, YieldAccumulator>>,
+    #[account(seeds = [b"staking_treasury"], bump)]
+    pub staking_treasury: Box<Account<'info, StakingTreasury>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimLamports<'info> {
+    #[account(mut, seeds = [b"user", payer.key().as_ref()], bump)]
+    pub user: Box<Account<'info, User>>,
+    #[account(mut, seeds = [b"total_won_points"], bump)]
+    pub total_won_points: Box<Account<'info, TotalWonPoints>>,
+    #[account(mut, seeds = [b"staking_treasury"], bump)]
+    pub staking_treasury: Box<Account<'info, StakingTreasury>>,
+    #[account(seeds = [b"yield_accumulator"], bump)]
+    pub yield_accumulator: Box<Account<'info, YieldAccumulator>>,
+    #[account(mut, seeds = [b"game_stats"], bump)]
+    pub game_stats: Box<Account<'info, GameStats>>,
+    // Token-mode players also pass their POINTS accounts, won points backed by tokens are burned on redemption
+    #[account(mut, seeds = [b"points_mint"], bump)]
+    pub points_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(
+        mut,
+        associated_token::mint = points_mint,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program,
+    )]
+    pub user_points_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeTransferConfig<'info> {
+    #[account(init_if_needed, payer = payer, space = TransferConfig::LEN, seeds = [b"transfer_config"], bump)]
+    pub transfer_config: Box<Account<'info, TransferConfig>>,
+    // Only the program's upgrade authority may configure point transfers and become their admin
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::PrimeSlotChecker>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(payer.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Box<Account<'info, ProgramData>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetTransferConfig<'info> {
+    #[account(mut, seeds = [b"transfer_config"], bump, has_one = admin @ ErrorCode::Unauthorized)]
+    pub transfer_config: Box<Account<'info, TransferConfig>>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, recipient: Pubkey)]
+pub struct TransferPoints<'info> {
+    #[account(mut, seeds = [b"user", payer.key().as_ref()], bump)]
+    pub user: Box<Account<'info, User>>,
+    #[account(mut, seeds = [b"user", recipient.as_ref()], bump)]
+    pub recipient_user: Box<Account<'info, User>>,
+    #[account(seeds = [b"transfer_config"], bump)]
+    pub transfer_config: Box<Account<'info, TransferConfig>>,
+    #[account(seeds = [b"yield_accumulator"], bump)]
+    pub yield_accumulator: Box<Account<'info, YieldAccumulator>>,
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeReferralVault<'info> {
+    #[account(init_if_needed, payer = payer, space = ReferralVault::LEN, seeds = [b"referral_vault"], bump)]
+    pub referral_vault: Box<Account<'info, ReferralVault>>,
+    // Only the program's upgrade authority may set the referral share and become its admin
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::PrimeSlotChecker>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(payer.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Box<Account<'info, ProgramData>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetReferralBps<'info> {
+    #[account(mut, seeds = [b"referral_vault"], bump, has_one = admin @ ErrorCode::Unauthorized)]
+    pub referral_vault: Box<Account<'info, ReferralVault>>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    #[account(init, payer = payer, space = ReferrerStats::LEN, seeds = [b"referrer", payer.key().as_ref()], bump)]
+    pub referrer_stats: Box<Account<'info, ReferrerStats>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimReferralRewards<'info> {
+    #[account(mut, seeds = [b"referrer", payer.key().as_ref()], bump)]
+    pub referrer_stats: Box<Account<'info, ReferrerStats>>,
+    #[account(mut, seeds = [b"referral_vault"], bump)]
+    pub referral_vault: Box<Account<'info, ReferralVault>>,
+    #[account(mut, seeds = [b"game_stats"], bump)]
+    pub game_stats: Box<Account<'info, GameStats>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeFeeVault<'info> {
+    #[account(init_if_needed, payer = payer, space = FeeVault::LEN, seeds = [b"fee_vault"], bump)]
+    pub fee_vault: Box<Account<'info, FeeVault>>,
+    // Only the program's upgrade authority may set protocol fees and become the fee admin
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::PrimeSlotChecker>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(payer.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Box<Account<'info, ProgramData>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetFees<'info> {
+    #[account(mut, seeds = [b"fee_vault"], bump, has_one = admin @ ErrorCode::Unauthorized)]
+    pub fee_vault: Box<Account<'info, FeeVault>>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    #[account(mut, seeds = [b"fee_vault"], bump, has_one = admin @ ErrorCode::Unauthorized)]
+    pub fee_vault: Box<Account<'info, FeeVault>>,
+    pub admin: Signer<'info>,
+    /// CHECK: any account the admin chooses to receive the fees
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeVestingConfig<'info> {
+    #[account(init_if_needed, payer = payer, space = VestingConfig::LEN, seeds = [b"vesting_config"], bump)]
+    pub vesting_config: Box<Account<'info, VestingConfig>>,
+    // Only the program's upgrade authority may configure vesting and become its admin
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::PrimeSlotChecker>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(payer.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Box<Account<'info, ProgramData>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetVestingConfig<'info> {
+    #[account(mut, seeds = [b"vesting_config"], bump, has_one = admin @ ErrorCode::Unauthorized)]
+    pub vesting_config: Box<Account<'info, VestingConfig>>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeVesting<'info> {
+    #[account(init_if_needed, payer = payer, space = Vesting::LEN, seeds = [b"vesting", payer.key().as_ref()], bump)]
+    pub vesting: Box<Account<'info, Vesting>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(mut, seeds = [b"vesting", payer.key().as_ref()], bump)]
+    pub vesting: Box<Account<'info, Vesting>>,
+    #[account(mut, seeds = [b"user", payer.key().as_ref()], bump)]
+    pub user: Box<Account<'info, User>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: any account owned by this program, its layout is identified by discriminator
+    #[account(mut, owner = crate::ID)]
+    pub target: UncheckedAccount<'info>,
+    #[account(seeds = [b"yield_accumulator"], bump)]
+    pub yield_accumulator: Box<Account<'info, YieldAccumulator>>,
+    // Anyone can migrate an account, the payer covers the extra rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseUser<'info> {
+    #[account(mut, close = payer, seeds = [b"user", payer.key().as_ref()], bump)]
+    pub user: Box<Account<'info, User>>,
+    #[account(mut, seeds = [b"jackpot"], bump)]
+    pub jackpot: Box<Account<'info, Jackpot>>,
     #[account(mut, seeds = [b"total_won_points"], bump)]
     pub total_won_points: Box<Account<'info, TotalWonPoints>>,
     #[account(mut, seeds = [b"staking_treasury"], bump)]
     pub staking_treasury: Box<Account<'info, StakingTreasury>>,
+    #[account(seeds = [b"yield_accumulator"], bump)]
+    pub yield_accumulator: Box<Account<'info, YieldAccumulator>>,
+    #[account(mut, seeds = [b"game_stats"], bump)]
+    pub game_stats: Box<Account<'info, GameStats>>,
+    // Token-mode players also pass their POINTS accounts, won points backed by tokens are burned on redemption
+    #[account(mut, seeds = [b"points_mint"], bump)]
+    pub points_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(
+        mut,
+        associated_token::mint = points_mint,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program,
+    )]
+    pub user_points_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    #[account(mut)]
     pub payer: Signer<'info>,
-    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemWonPoints<'info> {
+    #[account(mut, seeds = [b"user", payer.key().as_ref()], bump)]
+    pub user: Box<Account<'info, User>>,
+    #[account(mut, seeds = [b"total_won_points"], bump)]
+    pub total_won_points: Box<Account<'info, TotalWonPoints>>,
+    #[account(mut, seeds = [b"staking_treasury"], bump)]
+    pub staking_treasury: Box<Account<'info, StakingTreasury>>,
+    #[account(seeds = [b"yield_accumulator"], bump)]
+    pub yield_accumulator: Box<Account<'info, YieldAccumulator>>,
+    #[account(mut, seeds = [b"game_stats"], bump)]
+    pub game_stats: Box<Account<'info, GameStats>>,
+    // Token-mode players also pass their POINTS accounts, won points backed by tokens are burned on redemption
+    #[account(mut, seeds = [b"points_mint"], bump)]
+    pub points_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(
+        mut,
+        associated_token::mint = points_mint,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program,
+    )]
+    pub user_points_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QuoteRedemption<'info> {
+    #[account(seeds = [b"user", owner.key().as_ref()], bump)]
+    pub user: Box<Account<'info, User>>,
+    /// CHECK: only used to derive the quoted player's user PDA
+    pub owner: UncheckedAccount<'info>,
+    #[account(seeds = [b"yield_accumulator"], bump)]
+    pub yield_accumulator: Box<Account<'info, YieldAccumulator>>,
 }
 
 #[derive(Accounts)]
 pub struct TradeWonPoints<'info> {
     #[account(mut, seeds = [b"user", payer.key().as_ref()], bump)]
     pub user: Box<Account<'info, User>>,
-    #[account(mut, seeds = [b"rate"], bump)]
-    pub rate: Box<Account<'info, Rate>>,
     #[account(mut, seeds = [b"total_won_points"], bump)]
     pub total_won_points: Box<Account<'info, TotalWonPoints>>,
-    #[account(mut, seeds = [b"staking_treasury"], bump)]
-    pub staking_treasury: Box<Account<'info, StakingTreasury>>,
+    #[account(mut, seeds = [b"staking_treasury"], bump)]
+    pub staking_treasury: Box<Account<'info, StakingTreasury>>,
+    #[account(seeds = [b"yield_accumulator"], bump)]
+    pub yield_accumulator: Box<Account<'info, YieldAccumulator>>,
+    #[account(mut, seeds = [b"game_stats"], bump)]
+    pub game_stats: Box<Account<'info, GameStats>>,
+    // Token-mode players also pass their POINTS accounts, won points backed by tokens are burned on redemption
+    #[account(mut, seeds = [b"points_mint"], bump)]
+    pub points_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(
+        mut,
+        associated_token::mint = points_mint,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program,
+    )]
+    pub user_points_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+
+#[derive(Accounts)]
+pub struct InitializeSearchConfig<'info> {
+    #[account(init_if_needed, payer = payer, space = SearchConfig::LEN, seeds = [b"search_config"], bump)]
+    pub search_config: Box<Account<'info, SearchConfig>>,
+    #[account(init_if_needed, payer = payer, space = Difficulty::LEN, seeds = [b"search_difficulty"], bump)]
+    pub search_difficulty: Box<Account<'info, Difficulty>>,
+    // Only the program's upgrade authority may configure proof-of-search and become its admin
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::PrimeSlotChecker>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(payer.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Box<Account<'info, ProgramData>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetSearchDifficulty<'info> {
+    #[account(mut, seeds = [b"search_config"], bump, has_one = admin @ ErrorCode::Unauthorized)]
+    pub search_config: Box<Account<'info, SearchConfig>>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetProofPayout<'info> {
+    #[account(mut, seeds = [b"search_config"], bump, has_one = admin @ ErrorCode::Unauthorized)]
+    pub search_config: Box<Account<'info, SearchConfig>>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitPrimeProof<'info> {
+    #[account(mut, seeds = [b"user", payer.key().as_ref()], bump)]
+    pub user: Box<Account<'info, User>>,
+    #[account(init_if_needed, payer = payer, space = SearchState::LEN, seeds = [b"search_state", payer.key().as_ref()], bump)]
+    pub search_state: Box<Account<'info, SearchState>>,
+    #[account(seeds = [b"search_config"], bump)]
+    pub search_config: Box<Account<'info, SearchConfig>>,
+    #[account(mut, seeds = [b"jackpot"], bump)]
+    pub jackpot: Box<Account<'info, Jackpot>>,
+    #[account(mut, seeds = [b"total_won_points"], bump)]
+    pub total_won_points: Box<Account<'info, TotalWonPoints>>,
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+    #[account(mut, seeds = [b"player_list"], bump)]
+    pub player_list: AccountLoader<'info, PlayerList>,
+    #[account(mut, seeds = [b"staking_treasury"], bump)]
+    pub staking_treasury: Box<Account<'info, StakingTreasury>>,
+    #[account(seeds = [b"yield_accumulator"], bump)]
+    pub yield_accumulator: Box<Account<'info, YieldAccumulator>>,
+    /// CHECK: address is pinned to the SlotHashes sysvar, data is parsed by find_slot_hash
+    #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    #[account(seeds = [b"vesting_config"], bump)]
+    pub vesting_config: Box<Account<'info, VestingConfig>>,
+    // Created on the player's first round so a large win always has somewhere to vest
+    #[account(init_if_needed, payer = payer, space = Vesting::LEN, seeds = [b"vesting", payer.key().as_ref()], bump)]
+    pub vesting: Box<Account<'info, Vesting>>,
+    #[account(mut, seeds = [b"fee_vault"], bump)]
+    pub fee_vault: Box<Account<'info, FeeVault>>,
+    #[account(mut, seeds = [b"game_stats"], bump)]
+    pub game_stats: Box<Account<'info, GameStats>>,
+    #[account(mut, seeds = [b"win_history"], bump)]
+    pub win_history: AccountLoader<'info, WinHistory>,
+    #[account(seeds = [b"season_state"], bump)]
+    pub season_state: Box<Account<'info, SeasonState>>,
+    #[account(mut, seeds = [b"season", season_state.current_season.to_le_bytes().as_ref()], bump)]
+    pub season: Box<Account<'info, Season>>,
+    #[account(mut, seeds = [b"search_difficulty"], bump)]
+    pub search_difficulty: Box<Account<'info, Difficulty>>,
+    #[account(seeds = [b"epoch_state"], bump)]
+    pub epoch_state: Box<Account<'info, EpochState>>,
+    #[account(mut, seeds = [b"epoch", epoch_state.current_epoch.to_le_bytes().as_ref()], bump)]
+    pub epoch: Box<Account<'info, Epoch>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(table_id: u64)]
+pub struct CreateTable<'info> {
+    #[account(init, payer = authority, space = Table::LEN, seeds = [b"table", table_id.to_le_bytes().as_ref()], bump)]
+    pub table: Box<Account<'info, Table>>,
+    #[account(init, payer = authority, space = Jackpot::LEN, seeds = [b"jackpot", table_id.to_le_bytes().as_ref()], bump)]
+    pub jackpot: Box<Account<'info, Jackpot>>,
+    #[account(init, payer = authority, space = Treasury::LEN, seeds = [b"treasury", table_id.to_le_bytes().as_ref()], bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+    #[account(init, payer = authority, space = PlayerList::LEN, seeds = [b"player_list", table_id.to_le_bytes().as_ref()], bump)]
+    pub player_list: AccountLoader<'info, PlayerList>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(table_id: u64, player: Pubkey)]
+pub struct AddTablePlayer<'info> {
+    #[account(seeds = [b"table", table_id.to_le_bytes().as_ref()], bump, has_one = authority @ ErrorCode::Unauthorized)]
+    pub table: Box<Account<'info, Table>>,
+    #[account(init, payer = authority, space = TableSeat::LEN, seeds = [b"seat", table_id.to_le_bytes().as_ref(), player.as_ref()], bump)]
+    pub seat: Box<Account<'info, TableSeat>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(table_id: u64, player: Pubkey)]
+pub struct RemoveTablePlayer<'info> {
+    #[account(seeds = [b"table", table_id.to_le_bytes().as_ref()], bump, has_one = authority @ ErrorCode::Unauthorized)]
+    pub table: Box<Account<'info, Table>>,
+    #[account(mut, close = authority, seeds = [b"seat", table_id.to_le_bytes().as_ref(), player.as_ref()], bump)]
+    pub seat: Box<Account<'info, TableSeat>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(table_id: u64)]
+pub struct FundTable<'info> {
+    #[account(seeds = [b"table", table_id.to_le_bytes().as_ref()], bump)]
+    pub table: Box<Account<'info, Table>>,
+    #[account(mut, seeds = [b"treasury", table_id.to_le_bytes().as_ref()], bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(table_id: u64)]
+pub struct CheckTableSlot<'info> {
+    #[account(mut, seeds = [b"user", payer.key().as_ref()], bump)]
+    pub user: Box<Account<'info, User>>,
+    #[account(seeds = [b"table", table_id.to_le_bytes().as_ref()], bump)]
+    pub table: Box<Account<'info, Table>>,
+    #[account(seeds = [b"seat", table_id.to_le_bytes().as_ref(), payer.key().as_ref()], bump)]
+    pub seat: Option<Box<Account<'info, TableSeat>>>,
+    #[account(mut, seeds = [b"jackpot", table_id.to_le_bytes().as_ref()], bump)]
+    pub jackpot: Box<Account<'info, Jackpot>>,
+    #[account(mut, seeds = [b"treasury", table_id.to_le_bytes().as_ref()], bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+    #[account(mut, seeds = [b"player_list", table_id.to_le_bytes().as_ref()], bump)]
+    pub player_list: AccountLoader<'info, PlayerList>,
+    #[account(mut, seeds = [b"total_won_points"], bump)]
+    pub total_won_points: Box<Account<'info, TotalWonPoints>>,
+    #[account(mut, seeds = [b"staking_treasury"], bump)]
+    pub staking_treasury: Box<Account<'info, StakingTreasury>>,
+    #[account(seeds = [b"yield_accumulator"], bump)]
+    pub yield_accumulator: Box<Account<'info, YieldAccumulator>>,
+    #[account(seeds = [b"vesting_config"], bump)]
+    pub vesting_config: Box<Account<'info, VestingConfig>>,
+    // Created on the player's first round so a large win always has somewhere to vest
+    #[account(init_if_needed, payer = payer, space = Vesting::LEN, seeds = [b"vesting", payer.key().as_ref()], bump)]
+    pub vesting: Box<Account<'info, Vesting>>,
+    #[account(mut, seeds = [b"fee_vault"], bump)]
+    pub fee_vault: Box<Account<'info, FeeVault>>,
+    #[account(mut, seeds = [b"game_stats"], bump)]
+    pub game_stats: Box<Account<'info, GameStats>>,
+    #[account(mut, seeds = [b"difficulty"], bump)]
+    pub difficulty: Box<Account<'info, Difficulty>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(table_id: u64)]
+pub struct CloseTable<'info> {
+    #[account(mut, close = authority, seeds = [b"table", table_id.to_le_bytes().as_ref()], bump, has_one = authority @ ErrorCode::Unauthorized)]
+    pub table: Box<Account<'info, Table>>,
+    #[account(mut, close = authority, seeds = [b"jackpot", table_id.to_le_bytes().as_ref()], bump)]
+    pub jackpot: Box<Account<'info, Jackpot>>,
+    #[account(mut, close = authority, seeds = [b"treasury", table_id.to_le_bytes().as_ref()], bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+    #[account(mut, close = authority, seeds = [b"player_list", table_id.to_le_bytes().as_ref()], bump)]
+    pub player_list: AccountLoader<'info, PlayerList>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(guild_id: u64)]
+pub struct CreateGuild<'info> {
+    #[account(init, payer = payer, space = Guild::LEN, seeds = [b"guild", guild_id.to_le_bytes().as_ref()], bump)]
+    pub guild: Box<Account<'info, Guild>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(guild_id: u64)]
+pub struct JoinGuild<'info> {
+    #[account(mut, seeds = [b"guild", guild_id.to_le_bytes().as_ref()], bump)]
+    pub guild: Box<Account<'info, Guild>>,
+    // One guild per player, the member PDA is keyed by player only
+    #[account(init, payer = payer, space = GuildMember::LEN, seeds = [b"guild_member", payer.key().as_ref()], bump)]
+    pub guild_member: Box<Account<'info, GuildMember>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(guild_id: u64)]
+pub struct LeaveGuild<'info> {
+    #[account(mut, seeds = [b"guild", guild_id.to_le_bytes().as_ref()], bump)]
+    pub guild: Box<Account<'info, Guild>>,
+    #[account(mut, close = payer, seeds = [b"guild_member", payer.key().as_ref()], bump, constraint = guild_member.guild_id == guild_id @ ErrorCode::NotGuildMember)]
+    pub guild_member: Box<Account<'info, GuildMember>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeGuildLeaderboard<'info> {
+    #[account(init_if_needed, payer = payer, space = GuildLeaderboard::LEN, seeds = [b"guild_leaderboard"], bump)]
+    pub guild_leaderboard: Box<Account<'info, GuildLeaderboard>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(guild_id: u64, window_id: u64)]
+pub struct CheckGuildSlot<'info> {
+    pub game: CheckSlot<'info>,
+    #[account(mut, seeds = [b"guild", guild_id.to_le_bytes().as_ref()], bump)]
+    pub guild: Box<Account<'info, Guild>>,
+    #[account(mut, seeds = [b"guild_member", game.payer.key().as_ref()], bump, constraint = guild_member.guild_id == guild_id @ ErrorCode::NotGuildMember)]
+    pub guild_member: Box<Account<'info, GuildMember>>,
+    #[account(
+        init_if_needed,
+        payer = game.payer,
+        space = GuildWindow::LEN,
+        seeds = [b"guild_window", guild_id.to_le_bytes().as_ref(), window_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub guild_window: Box<Account<'info, GuildWindow>>,
+    #[account(mut, seeds = [b"guild_leaderboard"], bump)]
+    pub guild_leaderboard: Box<Account<'info, GuildLeaderboard>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(guild_id: u64, window_id: u64)]
+pub struct ClaimGuildRewards<'info> {
+    #[account(mut, seeds = [b"user", payer.key().as_ref()], bump)]
+    pub user: Box<Account<'info, User>>,
+    #[account(seeds = [b"guild", guild_id.to_le_bytes().as_ref()], bump)]
+    pub guild: Box<Account<'info, Guild>>,
+    #[account(mut, seeds = [b"guild_member", payer.key().as_ref()], bump, constraint = guild_member.guild_id == guild_id @ ErrorCode::NotGuildMember)]
+    pub guild_member: Box<Account<'info, GuildMember>>,
+    #[account(mut, seeds = [b"guild_window", guild_id.to_le_bytes().as_ref(), window_id.to_le_bytes().as_ref()], bump)]
+    pub guild_window: Box<Account<'info, GuildWindow>>,
+    #[account(seeds = [b"yield_accumulator"], bump)]
+    pub yield_accumulator: Box<Account<'info, YieldAccumulator>>,
+    #[account(mut, seeds = [b"total_won_points"], bump)]
+    pub total_won_points: Box<Account<'info, TotalWonPoints>>,
+    #[account(seeds = [b"staking_treasury"], bump)]
+    pub staking_treasury: Box<Account<'info, StakingTreasury>>,
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePointsMint<'info> {
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"points_mint"],
+        bump,
+        mint::decimals = 0,
+        mint::authority = mint_authority,
+        mint::token_program = token_program,
+    )]
+    pub points_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: PDA that signs mints, holds no data
+    #[account(seeds = [b"mint_authority"], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PayForPointTokens<'info> {
+    pub purchase: PayForPoints<'info>,
+    #[account(mut, seeds = [b"points_mint"], bump)]
+    pub points_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = points_mint,
+        associated_token::authority = purchase.payer,
+        associated_token::token_program = token_program,
+    )]
+    pub user_points_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: PDA that signs mints, holds no data
+    #[account(seeds = [b"mint_authority"], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CheckSlotTokens<'info> {
+    pub game: CheckSlot<'info>,
+    #[account(mut, seeds = [b"points_mint"], bump)]
+    pub points_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = points_mint,
+        associated_token::authority = game.payer,
+        associated_token::token_program = token_program,
+    )]
+    pub user_points_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct MigratePointsToTokens<'info> {
+    #[account(mut, seeds = [b"user", payer.key().as_ref()], bump)]
+    pub user: Box<Account<'info, User>>,
+    // Marker PDA, its init failing on a second call makes migration one-time
+    #[account(init, payer = payer, space = TokenMigration::LEN, seeds = [b"token_migration", payer.key().as_ref()], bump)]
+    pub token_migration: Box<Account<'info, TokenMigration>>,
+    #[account(mut, seeds = [b"points_mint"], bump)]
+    pub points_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = points_mint,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program,
+    )]
+    pub user_points_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: PDA that signs mints, holds no data
+    #[account(seeds = [b"mint_authority"], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(achievement: u8)]
+pub struct MintBadge<'info> {
+    #[account(mut, seeds = [b"user", payer.key().as_ref()], bump)]
+    pub user: Box<Account<'info, User>>,
+    /// CHECK: Created in the handler as a non-transferable Token-2022 mint, one per player and achievement
+    #[account(mut, seeds = [b"badge", payer.key().as_ref(), &[achievement]], bump)]
+    pub badge_mint: UncheckedAccount<'info>,
+    /// CHECK: The payer's associated token account for the badge, created and checked by the associated token program
+    #[account(mut)]
+    pub badge_account: UncheckedAccount<'info>,
+    /// CHECK: PDA that signs mints, holds no data
+    #[account(seeds = [b"mint_authority"], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePaymentConfig<'info> {
+    #[account(init_if_needed, payer = payer, space = PaymentConfig::LEN, seeds = [b"payment_config"], bump)]
+    pub payment_config: Box<Account<'info, PaymentConfig>>,
+    // Only the program's upgrade authority may become the admin of accepted SPL mints
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::PrimeSlotChecker>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(payer.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Box<Account<'info, ProgramData>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetAcceptedMint<'info> {
+    #[account(mut, seeds = [b"payment_config"], bump, has_one = admin @ ErrorCode::Unauthorized)]
+    pub payment_config: Box<Account<'info, PaymentConfig>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    // Vaults are created alongside the allowlist entry so buyers never pay their rent
+    #[account(
+        init_if_needed,
+        payer = admin,
+        seeds = [b"treasury_vault", mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+    )]
+    pub treasury_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        seeds = [b"staking_vault", mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+    )]
+    pub staking_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(init_if_needed, payer = admin, space = SplYieldPool::LEN, seeds = [b"spl_yield_pool", mint.key().as_ref()], bump)]
+    pub spl_yield_pool: Box<Account<'info, SplYieldPool>>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        seeds = [b"fee_token_vault", mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+    )]
+    pub fee_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: PDA that owns every token vault, holds no data
+    #[account(seeds = [b"vault_authority"], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveAcceptedMint<'info> {
+    #[account(mut, seeds = [b"payment_config"], bump, has_one = admin @ ErrorCode::Unauthorized)]
+    pub payment_config: Box<Account<'info, PaymentConfig>>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PayForPointsSpl<'info> {
+    #[account(mut, seeds = [b"user", payer.key().as_ref()], bump)]
+    pub user: Box<Account<'info, User>>,
+    #[account(seeds = [b"payment_config"], bump)]
+    pub payment_config: Box<Account<'info, PaymentConfig>>,
+    #[account(seeds = [b"pricing_config"], bump)]
+    pub pricing_config: Box<Account<'info, PricingConfig>>,
+    #[account(mut, seeds = [b"fee_vault"], bump)]
+    pub fee_vault: Box<Account<'info, FeeVault>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::authority = payer, token::token_program = token_program)]
+    pub payer_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, seeds = [b"treasury_vault", mint.key().as_ref()], bump)]
+    pub treasury_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, seeds = [b"staking_vault", mint.key().as_ref()], bump)]
+    pub staking_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, seeds = [b"fee_token_vault", mint.key().as_ref()], bump)]
+    pub fee_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, seeds = [b"spl_yield_pool", mint.key().as_ref()], bump)]
+    pub spl_yield_pool: Box<Account<'info, SplYieldPool>>,
+    // Needed on every purchase once the user has a referrer, their cut is paid straight to their token account
+    #[account(mut)]
+    pub referrer_stats: Option<Box<Account<'info, ReferrerStats>>>,
+    #[account(seeds = [b"referral_vault"], bump)]
+    pub referral_vault: Option<Box<Account<'info, ReferralVault>>>,
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub referrer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub payer: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct EnterSplYield<'info> {
+    #[account(mut, seeds = [b"user", payer.key().as_ref()], bump)]
+    pub user: Box<Account<'info, User>>,
+    #[account(seeds = [b"yield_accumulator"], bump)]
+    pub yield_accumulator: Box<Account<'info, YieldAccumulator>>,
+    #[account(mut, seeds = [b"total_won_points"], bump)]
+    pub total_won_points: Box<Account<'info, TotalWonPoints>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, seeds = [b"spl_yield_pool", mint.key().as_ref()], bump)]
+    pub spl_yield_pool: Box<Account<'info, SplYieldPool>>,
+    #[account(init_if_needed, payer = payer, space = SplYieldPosition::LEN, seeds = [b"spl_yield_position", mint.key().as_ref(), payer.key().as_ref()], bump)]
+    pub spl_yield_position: Box<Account<'info, SplYieldPosition>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemWonPointsSpl<'info> {
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, seeds = [b"spl_yield_pool", mint.key().as_ref()], bump)]
+    pub spl_yield_pool: Box<Account<'info, SplYieldPool>>,
+    #[account(mut, seeds = [b"spl_yield_position", mint.key().as_ref(), payer.key().as_ref()], bump)]
+    pub spl_yield_position: Box<Account<'info, SplYieldPosition>>,
+    #[account(mut, seeds = [b"staking_vault", mint.key().as_ref()], bump)]
+    pub staking_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, token::mint = mint, token::authority = payer, token::token_program = token_program)]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: PDA that owns every token vault, holds no data
+    #[account(seeds = [b"vault_authority"], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub payer: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSplVault<'info> {
+    #[account(seeds = [b"payment_config"], bump, has_one = admin @ ErrorCode::Unauthorized)]
+    pub payment_config: Box<Account<'info, PaymentConfig>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::authority = vault_authority, token::token_program = token_program)]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub recipient: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: PDA that owns every token vault, holds no data
+    #[account(seeds = [b"vault_authority"], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub admin: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(position_id: u64)]
+pub struct StakePoints<'info> {
+    #[account(mut, seeds = [b"user", payer.key().as_ref()], bump)]
+    pub user: Box<Account<'info, User>>,
+    #[account(init, payer = payer, space = StakePosition::LEN, seeds = [b"stake", payer.key().as_ref(), position_id.to_le_bytes().as_ref()], bump)]
+    pub stake_position: Box<Account<'info, StakePosition>>,
+    #[account(mut, seeds = [b"yield_accumulator"], bump)]
+    pub yield_accumulator: Box<Account<'info, YieldAccumulator>>,
+    #[account(mut, seeds = [b"total_won_points"], bump)]
+    pub total_won_points: Box<Account<'info, TotalWonPoints>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(position_id: u64)]
+pub struct UnstakePoints<'info> {
+    #[account(mut, seeds = [b"user", payer.key().as_ref()], bump)]
+    pub user: Box<Account<'info, User>>,
+    #[account(mut, seeds = [b"stake", payer.key().as_ref(), position_id.to_le_bytes().as_ref()], bump)]
+    pub stake_position: Box<Account<'info, StakePosition>>,
+    #[account(mut, seeds = [b"yield_accumulator"], bump)]
+    pub yield_accumulator: Box<Account<'info, YieldAccumulator>>,
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(position_id: u64)]
+pub struct WithdrawStake<'info> {
+    #[account(mut, seeds = [b"user", payer.key().as_ref()], bump)]
+    pub user: Box<Account<'info, User>>,
+    #[account(mut, close = payer, seeds = [b"stake", payer.key().as_ref(), position_id.to_le_bytes().as_ref()], bump)]
+    pub stake_position: Box<Account<'info, StakePosition>>,
+    #[account(seeds = [b"yield_accumulator"], bump)]
+    pub yield_accumulator: Box<Account<'info, YieldAccumulator>>,
+    #[account(mut, seeds = [b"total_won_points"], bump)]
+    pub total_won_points: Box<Account<'info, TotalWonPoints>>,
+    #[account(mut)]
     pub payer: Signer<'info>,
-    pub system_program: Program<'info, System>,
 }
 
-
 #[account]
+#[derive(Default, InitSpace)]
 pub struct User {
+    pub version: u8,
     pub points: i64,
     pub won_points: i64,
     pub last_won_slot: u64,
     pub last_claimed_slot: u64,
     pub last_claimed_lamports: u64,
+    pub reward_debt: u128,
+    pub pending_yield: u64,
+    pub referrer: Pubkey,
+    pub transfer_day: u64,
+    pub transferred_today: u64,
+    pub rounds_played: u64,
+    pub wins: u64,
+    pub superprimes: u64,
+    // Highest power-up any win was settled at
+    pub best_power_up_bps: u16,
+    // Round prizes only, vested part included. Epoch draws and season prizes are paid straight to
+    // the winner's wallet by a crank that never sees their User account, so they are not counted here.
+    pub lamports_won: u64,
+    // Yield redeemed plus vested lamports claimed, referral rewards are tracked on ReferrerStats instead
+    pub lamports_claimed: u64,
+    // Consecutive plays that won
+    pub current_streak: u64,
+    pub best_streak: u64,
+    pub first_play_slot: u64,
+    pub last_play_slot: u64,
+    // Bitmask of unlocked ACHIEVEMENT_* indices
+    pub achievements: u32,
+    // Bitmask of achievements whose badge was minted
+    pub badges_minted: u32,
+    // Won points earned in season `season_id`, the season's standings only index the top of these
+    pub season_id: u64,
+    pub season_points: u64,
 }
 
 #[account]
+#[derive(Default, InitSpace)]
 pub struct Jackpot {
+    pub version: u8,
     pub amount: i64,
     pub winner: Pubkey,
 }
 
 #[account]
-pub struct StakingTreasury {}
+#[derive(Default, InitSpace)]
+pub struct StakingTreasury {
+    pub version: u8,
+}
 
 #[account]
+#[derive(Default, InitSpace)]
 pub struct TotalWonPoints {
+    pub version: u8,
     pub points: u64,
 }
 
 #[account]
+#[derive(Default, InitSpace)]
 pub struct Treasury {
+    pub version: u8,
     pub amount: i64,
 }
 
-#[account]
+// Packed so the ring buffer is read in place at any offset, the LEN assertion below pins its byte layout
+#[account(zero_copy(unsafe))]
+#[allow(clippy::repr_packed_without_abi)]
 pub struct PlayerList {
-    pub players: Vec<Pubkey>,
+    pub version: u8,
+    // Index the next entry is written to
+    pub head: u16,
+    // Entries written so far, capped at PLAYER_LIST_CAPACITY
+    pub count: u16,
+    // Most recent entries summed into each round
+    pub window: u16,
+    pub admin: Pubkey,
+    // Sum of the numbers of the entries inside the window
+    pub window_sum: u64,
+    pub entries: [PlayerEntry; PLAYER_LIST_CAPACITY],
+}
+
+#[zero_copy(unsafe)]
+#[allow(clippy::repr_packed_without_abi)]
+pub struct PlayerEntry {
+    pub player: Pubkey,
+    pub slot: u64,
+    // pubkey_to_number of the player, what the round actually added
+    pub number: u64,
 }
 
 #[account]
+#[derive(Default, InitSpace)]
 pub struct Leaderboard {
+    pub version: u8,
+    #[max_len(MAX_LEADERBOARD_USERS)]
     pub users: Vec<UserEntry>,
 }
 
+// MasterChef-style accumulator: lamports of yield earned per won point, scaled by ACC_PRECISION
+#[account]
+#[derive(Default, InitSpace)]
+pub struct YieldAccumulator {
+    pub version: u8,
+    pub acc_reward_per_point: u128,
+    pub unallocated: u64,
+    pub total_stake_weight: u64,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct TransferConfig {
+    pub version: u8,
+    pub admin: Pubkey,
+    pub won_points_transferable: bool,
+    // Points a user can send per SLOTS_PER_DAY window
+    pub daily_cap: u64,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct ReferralVault {
+    pub version: u8,
+    pub admin: Pubkey,
+    pub referral_bps: u16,
+    pub total_earned: u64,
+    pub total_claimed: u64,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct ReferrerStats {
+    pub version: u8,
+    pub referrer: Pubkey,
+    pub referred_users: u64,
+    pub referred_purchases: u64,
+    // Lamports spent by referred users
+    pub referred_volume: u64,
+    pub earned: u64,
+    pub claimed: u64,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct FeeVault {
+    pub version: u8,
+    pub admin: Pubkey,
+    pub purchase_fee_bps: u16,
+    pub win_fee_bps: u16,
+    pub collected_from_purchases: u64,
+    pub collected_from_wins: u64,
+    pub withdrawn: u64,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct VestingConfig {
+    pub version: u8,
+    pub admin: Pubkey,
+    // Wins at or below this pay out in full immediately
+    pub threshold_lamports: u64,
+    pub immediate_bps: u16,
+    pub vesting_slots: u64,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct Vesting {
+    pub version: u8,
+    pub beneficiary: Pubkey,
+    // Released by earlier schedules but not yet claimed
+    pub unclaimed: u64,
+    // Releasing linearly from start_slot to end_slot
+    pub amount: u64,
+    pub claimed: u64,
+    pub start_slot: u64,
+    pub end_slot: u64,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct StakePosition {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub position_id: u64,
+    pub amount: u64,
+    // amount scaled by the lock tier multiplier, zero once unstaking starts
+    pub weight: u64,
+    pub lock_until_slot: u64,
+    pub unstake_requested_slot: u64,
+    pub reward_debt: u128,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct Difficulty {
+    pub version: u8,
+    pub value: u64,
+    pub target_win_interval: u64,
+    pub retarget_interval: u64,
+    pub wins_since_retarget: u64,
+    pub window_start_slot: u64,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct TokenMigration {
+    pub version: u8,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct PricingConfig {
+    pub version: u8,
+    pub admin: Pubkey,
+    pub lamports_per_1000: u64,
+    pub min_quantity: u64,
+    pub max_quantity: u64,
+    #[max_len(MAX_VOLUME_TIERS)]
+    pub volume_tiers: Vec<VolumeTier>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PricingParams {
+    pub lamports_per_1000: u64,
+    pub min_quantity: u64,
+    pub max_quantity: u64,
+    pub volume_tiers: Vec<VolumeTier>,
+}
+
+// Purchases of at least min_quantity points get discount_bps off
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct VolumeTier {
+    pub min_quantity: u64,
+    pub discount_bps: u16,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct PaymentConfig {
+    pub version: u8,
+    pub admin: Pubkey,
+    pub treasury_bps: u16,
+    #[max_len(MAX_ACCEPTED_MINTS)]
+    pub accepted_mints: Vec<AcceptedMint>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct AcceptedMint {
+    pub mint: Pubkey,
+    pub price_per_1000: u64,
+}
+
+// YieldAccumulator for one mint's yield vault, shared by the won points put behind that mint
+#[account]
+#[derive(Default, InitSpace)]
+pub struct SplYieldPool {
+    pub version: u8,
+    pub acc_reward_per_point: u128,
+    pub unallocated: u64,
+    pub enrolled_points: u64,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct SplYieldPosition {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub reward_debt: u128,
+    pub pending_tokens: u64,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct Table {
+    pub version: u8,
+    pub table_id: u64,
+    pub authority: Pubkey,
+    pub config: TableConfig,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct TableConfig {
+    pub entry_cost: u64,
+    // Power-up for new users and anyone below the first tier
+    pub base_power_up_bps: u16,
+    // Ascending by min_slots; the highest tier reached since the last win applies
+    pub power_up_tiers: [PowerUpTier; 3],
+    // Zero digits disables SuperPrimes at this table
+    pub superprime_digits: u8,
+    pub superprime_suffix: u64,
+    pub private: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct PowerUpTier {
+    pub min_slots: u64,
+    pub bps: u16,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct TableSeat {
+    pub version: u8,
+    pub table_id: u64,
+    pub player: Pubkey,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct Guild {
+    pub version: u8,
+    pub guild_id: u64,
+    pub founder: Pubkey,
+    // Contributions are counted per window of this many slots
+    pub window_slots: u64,
+    pub member_count: u64,
+    pub total_won_points: u64,
+    pub last_won_slot: u64,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct GuildMember {
+    pub version: u8,
+    pub guild_id: u64,
+    pub player: Pubkey,
+    pub joined_slot: u64,
+    pub window_id: u64,
+    // Points spent on guild rounds during window_id
+    pub contribution: u64,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct GuildWindow {
+    pub version: u8,
+    pub guild_id: u64,
+    pub window_id: u64,
+    pub total_contribution: u64,
+    pub pool: u64,
+    pub claimed: u64,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct GuildLeaderboard {
+    pub version: u8,
+    #[max_len(MAX_GUILD_LEADERBOARD)]
+    pub guilds: Vec<GuildEntry>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct GuildEntry {
+    pub guild_id: u64,
+    pub won_points: u64,
+}
+
+// Holds the lamports rolled forward between epochs
+#[account]
+#[derive(Default, InitSpace)]
+pub struct EpochState {
+    pub version: u8,
+    pub current_epoch: u64,
+    pub epoch_length: u64,
+    pub draw_bps: u16,
+}
+
+// One per epoch id; kept after settlement as the archive of that draw
+#[account]
+#[derive(Default, InitSpace)]
+pub struct Epoch {
+    pub version: u8,
+    pub epoch_id: u64,
+    pub start_slot: u64,
+    pub end_slot: u64,
+    pub carried_in: u64,
+    pub pot: u64,
+    pub rolled_forward: u64,
+    pub rounds: u64,
+    #[max_len(MAX_EPOCH_WINNERS)]
+    pub winners: Vec<EpochWinner>,
+    pub settled: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct EpochWinner {
+    pub winner: Pubkey,
+    pub primes: u64,
+    pub payout: u64,
+}
+
+// Holds the season prize pool and whatever rolled forward between seasons
+#[account]
+#[derive(Default, InitSpace)]
+pub struct SeasonState {
+    pub version: u8,
+    pub admin: Pubkey,
+    pub current_season: u64,
+    pub season_length: u64,
+    // Slice of each purchase, after the protocol fee, paid into the pool
+    pub pool_bps: u16,
+    // Lamports collected during the current season
+    pub pool: u64,
+    // Share of the pot in bps for each rank, best first
+    #[max_len(MAX_SEASON_STANDINGS)]
+    pub distribution: Vec<u16>,
+}
+
+// One per season id; kept after finalization as the archive of its final standings
 #[account]
-pub struct Rate {
-    pub value: f64,
+#[derive(Default, InitSpace)]
+pub struct Season {
+    pub version: u8,
+    pub season_id: u64,
+    pub start_slot: u64,
+    pub end_slot: u64,
+    pub carried_in: u64,
+    pub pot: u64,
+    pub paid: u64,
+    pub rolled_forward: u64,
+    #[max_len(MAX_SEASON_STANDINGS)]
+    pub standings: Vec<SeasonEntry>,
+    pub finalized: bool,
+    // Wins after end_slot but before finalization, ranked by next season's points; they seed its standings
+    #[max_len(MAX_SEASON_STANDINGS)]
+    pub late_standings: Vec<SeasonEntry>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct SeasonEntry {
+    pub player: Pubkey,
+    pub won_points: u64,
+    pub payout: u64,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct SearchConfig {
+    pub version: u8,
+    pub admin: Pubkey,
+    pub suffix_digits: u8,
+    pub suffix: u64,
+    // Share of a regular win's treasury payout a proof earns
+    pub payout_bps: u16,
+    // Lamports above rent that proofs can never pay out of the treasury
+    pub treasury_floor: u64,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct SearchState {
+    pub version: u8,
+    pub last_proof_slot: u64,
+    pub proofs: u64,
+}
+
+// Protocol-wide counters, one per program
+#[account]
+#[derive(Default, InitSpace)]
+pub struct GameStats {
+    pub version: u8,
+    pub total_rounds: u64,
+    pub total_wins: u64,
+    pub total_superprimes: u64,
+    // Lamports paid for points, before fees and referral cuts
+    pub total_deposited: u64,
+    // Lamports paid to players: round wins (vested part included), epoch draws, season prizes and referral rewards
+    pub total_paid_out: u64,
+    pub total_yield_claimed: u64,
+}
+
+// Packed like PlayerList, the LEN assertion below pins its byte layout
+#[account(zero_copy(unsafe))]
+#[allow(clippy::repr_packed_without_abi)]
+pub struct WinHistory {
+    pub version: u8,
+    // Index the next win is written to
+    pub head: u16,
+    // Wins written so far, capped at WIN_HISTORY_CAPACITY
+    pub count: u16,
+    pub entries: [WinEntry; WIN_HISTORY_CAPACITY],
+}
+
+#[zero_copy(unsafe)]
+#[allow(clippy::repr_packed_without_abi)]
+pub struct WinEntry {
+    pub winner: Pubkey,
+    pub slot: u64,
+    pub number: u64,
+    pub power_up_bps: u16,
+    // Whole prize after fees, vested part included
+    pub lamports: u64,
+    pub points: u64,
+    // PRIME_KIND_PRIME or PRIME_KIND_SUPERPRIME
+    pub kind: u8,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct GameStatsSnapshot {
+    pub slot: u64,
+    pub total_rounds: u64,
+    pub total_wins: u64,
+    pub total_superprimes: u64,
+    pub total_deposited: u64,
+    pub total_paid_out: u64,
+    pub total_yield_claimed: u64,
+    pub treasury_balance: u64,
+    pub staking_treasury_balance: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
 pub struct UserEntry {
     pub user: Pubkey,
     pub points: i64,
 }
 
 impl PlayerList {
-    const LEN: usize = 8 + (32 * 20) + 32; // Discriminator + 20 Pubkeys
+    const LEN: usize = 8 + std::mem::size_of::<PlayerList>();
+    const VERSION: u8 = 2;
+
+    // Entry `back` rounds before the newest one, 0 being the newest
+    fn recent(&self, back: usize) -> &PlayerEntry {
+        &self.entries[(self.head as usize + PLAYER_LIST_CAPACITY - 1 - back) % PLAYER_LIST_CAPACITY]
+    }
+
+    fn push(&mut self, player: Pubkey, slot: u64) {
+        // The oldest entry inside the window leaves the sum before its slot can be overwritten
+        if self.count >= self.window {
+            self.window_sum -= self.recent(self.window as usize - 1).number;
+        }
+
+        let number = pubkey_to_number(&player) as u64;
+        self.entries[self.head as usize] = PlayerEntry { player, slot, number };
+        self.head = ((self.head as usize + 1) % PLAYER_LIST_CAPACITY) as u16;
+        self.count = (self.count + 1).min(PLAYER_LIST_CAPACITY as u16);
+        self.window_sum += number;
+    }
+
+    fn set_window(&mut self, window: u16) {
+        self.window = window;
+        self.window_sum = (0..self.count.min(window) as usize).map(|back| self.recent(back).number).sum();
+    }
 }
 
 impl User {
-    const LEN: usize = 8 * 6;
+    const LEN: usize = 8 + User::INIT_SPACE;
+    const VERSION: u8 = 4;
 }
 
 impl Jackpot {
-    const LEN: usize = 8 + 8 + 32; // Discriminator + amount + Pubkey
+    const LEN: usize = 8 + Jackpot::INIT_SPACE;
+    const VERSION: u8 = 1;
 }
 
 impl TotalWonPoints {
-    const LEN: usize = 8 + 8; // Discriminator + amount
+    const LEN: usize = 8 + TotalWonPoints::INIT_SPACE;
+    const VERSION: u8 = 1;
 }
 
 impl Treasury {
-    const LEN: usize = 8 + 8; // Discriminator + amount
+    const LEN: usize = 8 + Treasury::INIT_SPACE;
+    const VERSION: u8 = 1;
 }
 
 impl Leaderboard {
-    const LEN: usize = 8 + (32 + 8) * 100; // Discriminator + 100 UserEntry
+    const LEN: usize = 8 + Leaderboard::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl YieldAccumulator {
+    const LEN: usize = 8 + YieldAccumulator::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl TransferConfig {
+    const LEN: usize = 8 + TransferConfig::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl ReferralVault {
+    const LEN: usize = 8 + ReferralVault::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl ReferrerStats {
+    const LEN: usize = 8 + ReferrerStats::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl FeeVault {
+    const LEN: usize = 8 + FeeVault::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl VestingConfig {
+    const LEN: usize = 8 + VestingConfig::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl Vesting {
+    const LEN: usize = 8 + Vesting::INIT_SPACE;
+    const VERSION: u8 = 1;
 }
 
-impl Rate {
-    const LEN: usize = 8 + 8; // Discriminator + rate value
+impl StakePosition {
+    const LEN: usize = 8 + StakePosition::INIT_SPACE;
+    const VERSION: u8 = 1;
 }
 
 impl StakingTreasury {
-    const LEN: usize = 8; // Discriminator
+    const LEN: usize = 8 + StakingTreasury::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl Difficulty {
+    const LEN: usize = 8 + Difficulty::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl TokenMigration {
+    const LEN: usize = 8 + TokenMigration::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl PricingConfig {
+    const LEN: usize = 8 + PricingConfig::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl PaymentConfig {
+    const LEN: usize = 8 + PaymentConfig::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl SplYieldPool {
+    const LEN: usize = 8 + SplYieldPool::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl SplYieldPosition {
+    const LEN: usize = 8 + SplYieldPosition::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl Table {
+    const LEN: usize = 8 + Table::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl TableSeat {
+    const LEN: usize = 8 + TableSeat::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl Guild {
+    const LEN: usize = 8 + Guild::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl GuildMember {
+    const LEN: usize = 8 + GuildMember::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl GuildWindow {
+    const LEN: usize = 8 + GuildWindow::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl GuildLeaderboard {
+    const LEN: usize = 8 + GuildLeaderboard::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl EpochState {
+    const LEN: usize = 8 + EpochState::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl Epoch {
+    const LEN: usize = 8 + Epoch::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl SearchConfig {
+    const LEN: usize = 8 + SearchConfig::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl SearchState {
+    const LEN: usize = 8 + SearchState::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl GameStats {
+    const LEN: usize = 8 + GameStats::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl SeasonState {
+    const LEN: usize = 8 + SeasonState::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl Season {
+    const LEN: usize = 8 + Season::INIT_SPACE;
+    const VERSION: u8 = 1;
+}
+
+impl WinHistory {
+    const LEN: usize = 8 + std::mem::size_of::<WinHistory>();
+    const VERSION: u8 = 1;
+
+    fn push(&mut self, entry: WinEntry) {
+        self.entries[self.head as usize] = entry;
+        self.head = ((self.head as usize + 1) % WIN_HISTORY_CAPACITY) as u16;
+        self.count = (self.count + 1).min(WIN_HISTORY_CAPACITY as u16);
+    }
 }
 
+// Compile-time check of every LEN against the Borsh size of a fully populated account
+// Discriminator + version + 5 legacy fields + reward debt + pending yield + referrer + transfer day + transferred today
+// + rounds + wins + SuperPrimes + best power-up + lamports won + claimed + streaks + first and last play slot
+// + achievement and badge bitmasks + season id and points
+const _: () = assert!(User::LEN == 8 + 1 + 8 * 5 + 16 + 8 + 32 + 8 + 8 + 8 * 3 + 2 + 8 * 6 + 4 * 2 + 8 * 2);
+// Discriminator + version + amount + Pubkey
+const _: () = assert!(Jackpot::LEN == 8 + 1 + 8 + 32);
+// Discriminator + version
+const _: () = assert!(StakingTreasury::LEN == 8 + 1);
+// Discriminator + version + amount
+const _: () = assert!(TotalWonPoints::LEN == 8 + 1 + 8);
+// Discriminator + version + amount
+const _: () = assert!(Treasury::LEN == 8 + 1 + 8);
+// Discriminator + version + head + count + window + admin + window sum + entries
+const _: () = assert!(PlayerList::LEN == 8 + 1 + 2 * 3 + 32 + 8 + (32 + 8 + 8) * PLAYER_LIST_CAPACITY);
+// Discriminator + version + vec prefix + UserEntry
+const _: () = assert!(Leaderboard::LEN == 8 + 1 + 4 + (32 + 8) * MAX_LEADERBOARD_USERS);
+// Discriminator + version + accumulator + unallocated + stake weight
+const _: () = assert!(YieldAccumulator::LEN == 8 + 1 + 16 + 8 + 8);
+// Discriminator + version + admin + won points flag + daily cap
+const _: () = assert!(TransferConfig::LEN == 8 + 1 + 32 + 1 + 8);
+// Discriminator + version + admin + bps + earned + claimed
+const _: () = assert!(ReferralVault::LEN == 8 + 1 + 32 + 2 + 8 + 8);
+// Discriminator + version + referrer + users + purchases + volume + earned + claimed
+const _: () = assert!(ReferrerStats::LEN == 8 + 1 + 32 + 8 * 5);
+// Discriminator + version + admin + fee bps + collected + withdrawn
+const _: () = assert!(FeeVault::LEN == 8 + 1 + 32 + 2 + 2 + 8 * 3);
+// Discriminator + version + admin + threshold + immediate bps + vesting slots
+const _: () = assert!(VestingConfig::LEN == 8 + 1 + 32 + 8 + 2 + 8);
+// Discriminator + version + beneficiary + unclaimed + amount + claimed + start + end
+const _: () = assert!(Vesting::LEN == 8 + 1 + 32 + 8 * 5);
+// Discriminator + version + owner + id + amount + weight + lock until + unstake requested + reward debt
+const _: () = assert!(StakePosition::LEN == 8 + 1 + 32 + 8 * 5 + 16);
+// Discriminator + version + value + target interval + retarget interval + wins + window start
+const _: () = assert!(Difficulty::LEN == 8 + 1 + 8 * 5);
+// Discriminator + version
+const _: () = assert!(TokenMigration::LEN == 8 + 1);
+// Discriminator + version + admin + price + min + max + volume tiers
+const _: () = assert!(PricingConfig::LEN == 8 + 1 + 32 + 8 * 3 + 4 + (8 + 2) * MAX_VOLUME_TIERS);
+// Discriminator + version + admin + treasury bps + accepted mints
+const _: () = assert!(PaymentConfig::LEN == 8 + 1 + 32 + 2 + 4 + (32 + 8) * MAX_ACCEPTED_MINTS);
+// Discriminator + version + accumulator + unallocated + enrolled points
+const _: () = assert!(SplYieldPool::LEN == 8 + 1 + 16 + 8 + 8);
+// Discriminator + version + owner + amount + reward debt + pending tokens
+const _: () = assert!(SplYieldPosition::LEN == 8 + 1 + 32 + 8 + 16 + 8);
+// Discriminator + version + id + authority + entry cost + base bps + 3 tiers + SuperPrime digits + suffix + private
+const _: () = assert!(Table::LEN == 8 + 1 + 8 + 32 + 8 + 2 + (8 + 2) * 3 + 1 + 8 + 1);
+// Discriminator + version + table id + player
+const _: () = assert!(TableSeat::LEN == 8 + 1 + 8 + 32);
+// Discriminator + version + id + founder + window + members + won points + last won slot
+const _: () = assert!(Guild::LEN == 8 + 1 + 8 + 32 + 8 * 4);
+// Discriminator + version + guild id + player + joined + window + contribution
+const _: () = assert!(GuildMember::LEN == 8 + 1 + 8 + 32 + 8 * 3);
+// Discriminator + version + guild id + window id + contribution + pool + claimed
+const _: () = assert!(GuildWindow::LEN == 8 + 1 + 8 * 5);
+// Discriminator + version + vec prefix + entries
+const _: () = assert!(GuildLeaderboard::LEN == 8 + 1 + 4 + (8 + 8) * MAX_GUILD_LEADERBOARD);
+// Discriminator + version + current epoch + length + draw bps
+const _: () = assert!(EpochState::LEN == 8 + 1 + 8 + 8 + 2);
+// Discriminator + version + id + start + end + carried in + pot + rolled forward + rounds + winners + settled
+const _: () = assert!(Epoch::LEN == 8 + 1 + 8 * 7 + 4 + (32 + 8 + 8) * MAX_EPOCH_WINNERS + 1);
+// Discriminator + version + admin + suffix digits + suffix + payout bps + treasury floor
+const _: () = assert!(SearchConfig::LEN == 8 + 1 + 32 + 1 + 8 + 2 + 8);
+// Discriminator + version + last proof slot + proofs
+const _: () = assert!(SearchState::LEN == 8 + 1 + 8 + 8);
+// Discriminator + version + rounds + wins + SuperPrimes + deposited + paid out + yield claimed
+const _: () = assert!(GameStats::LEN == 8 + 1 + 8 * 6);
+// Discriminator + version + admin + current season + length + pool bps + pool + distribution
+const _: () = assert!(SeasonState::LEN == 8 + 1 + 32 + 8 + 8 + 2 + 8 + 4 + 2 * MAX_SEASON_STANDINGS);
+// Discriminator + version + id + start/end + carried in + pot + paid + rolled forward + standings + finalized + late standings
+const _: () = assert!(Season::LEN == 8 + 1 + 8 * 7 + 4 + (32 + 8 + 8) * MAX_SEASON_STANDINGS + 1 + 4 + (32 + 8 + 8) * MAX_SEASON_STANDINGS);
+// Discriminator + version + head + count + entries of winner, slot, number, power-up, lamports, points and kind
+const _: () = assert!(WinHistory::LEN == 8 + 1 + 2 + 2 + (32 + 8 + 8 + 2 + 8 + 8 + 1) * WIN_HISTORY_CAPACITY);
+
 #[event]
 pub struct PrimeFound {
     pub slot: u64,
@@ -618,6 +4956,97 @@ pub struct PrimeFound {
     pub reward_points: i64,
 }
 
+#[event]
+pub struct EpochClosed {
+    pub epoch_id: u64,
+    pub rounds: u64,
+    pub winners: u8,
+    pub pot: u64,
+    pub paid: u64,
+    pub rolled_forward: u64,
+}
+
+#[event]
+pub struct SeasonFinalized {
+    pub season_id: u64,
+    pub ranked: u8,
+    pub pot: u64,
+    pub paid: u64,
+    pub rolled_forward: u64,
+}
+
+#[event]
+pub struct DifficultyAdjusted {
+    pub slot: u64,
+    pub old_difficulty: u64,
+    pub new_difficulty: u64,
+    pub wins: u64,
+    pub actual_slots: u64,
+    pub expected_slots: u64,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub old_len: u32,
+    pub new_len: u32,
+    pub version: u8,
+}
+
+#[event]
+pub struct PointsTransferred {
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+    pub won_points: u64,
+}
+
+#[event]
+pub struct ReferralRewarded {
+    pub referrer: Pubkey,
+    pub buyer: Pubkey,
+    pub purchase_lamports: u64,
+    pub reward: u64,
+}
+
+#[event]
+pub struct FeeCollected {
+    pub payer: Pubkey,
+    pub amount: u64,
+    pub from_win: bool,
+}
+
+#[event]
+pub struct FeesWithdrawn {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub total_withdrawn: u64,
+}
+
+#[event]
+pub struct BadgeMinted {
+    pub player: Pubkey,
+    pub achievement: u8,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct WonPointsRedeemed {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub lamports_out: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct SplWonPointsRedeemed {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub tokens_out: u64,
+    pub slot: u64,
+}
+
 // Convert a public key to a number in the range of 1 to 100,000
 fn pubkey_to_number(pubkey: &Pubkey) -> u32 {
     let mut number: u32 = 0;
@@ -627,3 +5056,248 @@ fn pubkey_to_number(pubkey: &Pubkey) -> u32 {
     (number % 100_000) + 1
 }
 
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Batch size must be at least 1")]
+    InvalidBatchSize,
+    #[msg("Batch would exceed the compute budget")]
+    BatchExceedsComputeBudget,
+    #[msg("Signer is not the config admin")]
+    Unauthorized,
+    #[msg("Suffix must be smaller than 10^digits, and a proof suffix can't match the SuperPrime ending")]
+    InvalidSuffix,
+    #[msg("Slot hash is not in the SlotHashes sysvar")]
+    SlotHashNotFound,
+    #[msg("A proof for this or a later slot was already submitted")]
+    StaleProof,
+    #[msg("Candidate does not end in the required digits")]
+    SuffixMismatch,
+    #[msg("Candidate is not prime")]
+    NotPrime,
+    #[msg("Target win interval and retarget interval must be non-zero")]
+    InvalidDifficultyParams,
+    #[msg("Epoch length must be non-zero and draw share at most 2000 bps")]
+    InvalidEpochParams,
+    #[msg("Epoch has not reached its end slot")]
+    EpochStillOpen,
+    #[msg("Remaining accounts must be the epoch winners in recorded order")]
+    WinnerAccountMismatch,
+    #[msg("Season length must be non-zero, pool share at most 2000 bps and the distribution 1 to 10 ranks summing to at most 10000 bps")]
+    InvalidSeasonParams,
+    #[msg("Season has not reached its end slot")]
+    SeasonStillOpen,
+    #[msg("Table config has a zero entry cost, a power-up above 100% or unordered tiers")]
+    InvalidTableConfig,
+    #[msg("Player has no seat at this private table")]
+    NotSeated,
+    #[msg("Treasury share above 10000 bps or zero price")]
+    InvalidPaymentConfig,
+    #[msg("Accepted mint list is full")]
+    TooManyAcceptedMints,
+    #[msg("Mint is not accepted for point purchases")]
+    MintNotAccepted,
+    #[msg("Pricing needs a non-zero price, min <= max and discounts below 100%")]
+    InvalidPricing,
+    #[msg("Quantity is outside the configured purchase range")]
+    QuantityOutOfRange,
+    #[msg("Price exceeds max_lamports")]
+    SlippageExceeded,
+    #[msg("Lock is shorter than the first staking tier")]
+    LockTooShort,
+    #[msg("Stake is still locked")]
+    StakeLocked,
+    #[msg("Unstake was already requested for this position")]
+    UnstakeAlreadyRequested,
+    #[msg("Stake has not finished its unstake cooldown")]
+    StakeCoolingDown,
+    #[msg("Redemption amount exceeds won points")]
+    InvalidRedemption,
+    #[msg("Won points are out of sync with points")]
+    PointsInvariantViolated,
+    #[msg("Immediate share must be at most 10000 bps and vesting must last at least one slot")]
+    InvalidVestingConfig,
+    #[msg("Nothing has vested yet")]
+    NothingVested,
+    #[msg("Fee exceeds the maximum")]
+    FeeTooHigh,
+    #[msg("Referral share exceeds the maximum")]
+    ReferralTooHigh,
+    #[msg("Users cannot refer themselves")]
+    InvalidReferrer,
+    #[msg("Referral accounts do not match the user's referrer")]
+    ReferrerMismatch,
+    #[msg("No referral rewards to claim")]
+    NoReferralRewards,
+    #[msg("Cannot transfer points to yourself")]
+    SelfTransfer,
+    #[msg("Won points are not transferable")]
+    WonPointsNotTransferable,
+    #[msg("Daily transfer cap exceeded")]
+    DailyTransferCapExceeded,
+    #[msg("Guild reward window must be at least one slot")]
+    InvalidGuildWindow,
+    #[msg("Player is not a member of this guild")]
+    NotGuildMember,
+    #[msg("Window is not the member's current guild window")]
+    WrongGuildWindow,
+    #[msg("Guild window is still open")]
+    GuildWindowOpen,
+    #[msg("Claim guild rewards from the previous window first")]
+    GuildRewardsUnclaimed,
+    #[msg("Guild members must wait out the cooldown before leaving")]
+    GuildLeaveCooldown,
+    #[msg("User still holds points")]
+    UserNotEmpty,
+    #[msg("Account is not a known account type of this program")]
+    UnknownAccountLayout,
+    #[msg("Account is already at the current layout version")]
+    AlreadyMigrated,
+    #[msg("Player window must be between 1 and the player list capacity")]
+    InvalidPlayerWindow,
+    #[msg("Unknown achievement")]
+    InvalidAchievement,
+    #[msg("Achievement is not unlocked yet")]
+    AchievementLocked,
+    #[msg("Badge for this achievement was already minted")]
+    BadgeAlreadyMinted,
+    #[msg("Table treasury still holds lamports beyond rent")]
+    TableTreasuryNotEmpty,
+    #[msg("Total won points would drop below zero")]
+    TotalWonPointsUnderflow,
+    #[msg("Pass the points mint, the POINTS token account and the token program together")]
+    IncompletePointTokenAccounts,
+    #[msg("Only a mint's treasury and fee vaults can be withdrawn")]
+    InvalidVault,
+    #[msg("Yield accounting overflowed")]
+    YieldOverflow,
+    #[msg("Candidate does not pass the proof-of-search difficulty")]
+    ProofBelowDifficulty,
+    #[msg("Proof payout share must be at most 10000 bps")]
+    InvalidProofPayout,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Serialized size of an account as it is stored on chain, discriminator included
+    fn stored_len<T: AnchorSerialize>(account: &T) -> usize {
+        8 + account.try_to_vec().unwrap().len()
+    }
+
+    macro_rules! assert_fixed_len {
+        ($($account:ident),* $(,)?) => {
+            $(assert_eq!(stored_len(&$account::default()), $account::LEN, stringify!($account));)*
+        };
+    }
+
+    #[test]
+    fn fixed_size_accounts_fill_their_len() {
+        assert_fixed_len!(
+            User, Jackpot, StakingTreasury, TotalWonPoints, Treasury, YieldAccumulator, TransferConfig,
+            ReferralVault, ReferrerStats, FeeVault, VestingConfig, Vesting, StakePosition, Difficulty,
+            TokenMigration, Table, TableSeat, Guild, GuildMember, GuildWindow, EpochState, SearchConfig,
+            SearchState, GameStats, SplYieldPool, SplYieldPosition,
+        );
+    }
+
+    #[test]
+    fn accounts_with_full_vecs_fill_their_len() {
+        let leaderboard = Leaderboard { users: vec![UserEntry::default(); MAX_LEADERBOARD_USERS], ..Default::default() };
+        assert_eq!(stored_len(&leaderboard), Leaderboard::LEN);
+
+        let pricing_config = PricingConfig { volume_tiers: vec![VolumeTier::default(); MAX_VOLUME_TIERS], ..Default::default() };
+        assert_eq!(stored_len(&pricing_config), PricingConfig::LEN);
+
+        let payment_config = PaymentConfig { accepted_mints: vec![AcceptedMint::default(); MAX_ACCEPTED_MINTS], ..Default::default() };
+        assert_eq!(stored_len(&payment_config), PaymentConfig::LEN);
+
+        let guild_leaderboard = GuildLeaderboard { guilds: vec![GuildEntry::default(); MAX_GUILD_LEADERBOARD], ..Default::default() };
+        assert_eq!(stored_len(&guild_leaderboard), GuildLeaderboard::LEN);
+
+        let epoch = Epoch { winners: vec![EpochWinner::default(); MAX_EPOCH_WINNERS], ..Default::default() };
+        assert_eq!(stored_len(&epoch), Epoch::LEN);
+
+        let season_state = SeasonState { distribution: vec![0; MAX_SEASON_STANDINGS], ..Default::default() };
+        assert_eq!(stored_len(&season_state), SeasonState::LEN);
+
+        let season = Season {
+            standings: vec![SeasonEntry::default(); MAX_SEASON_STANDINGS],
+            late_standings: vec![SeasonEntry::default(); MAX_SEASON_STANDINGS],
+            ..Default::default()
+        };
+        assert_eq!(stored_len(&season), Season::LEN);
+    }
+
+    #[test]
+    fn capped_payouts_never_take_the_treasury_below_its_floor() {
+        let cap = PayoutCap { bps: 5_000, floor: 1_000_000 };
+        let mut above_rent = 10_000_000u64;
+        for _ in 0..1_000 {
+            above_rent -= win_payout(above_rent, false, 1.0, cap);
+            assert!(above_rent >= cap.floor);
+        }
+        assert_eq!(win_payout(cap.floor, true, 1.0, cap), 0);
+        assert_eq!(win_payout(10_000, false, 0.5, PayoutCap::FULL), 5_000);
+    }
+
+    #[test]
+    fn spl_yield_pays_each_position_only_what_was_deposited_while_it_was_in() {
+        let mut pool = SplYieldPool { unallocated: 500, ..Default::default() };
+        let mut early = SplYieldPosition::default();
+        let mut late = SplYieldPosition::default();
+        let enter = |position: &mut SplYieldPosition, pool: &mut SplYieldPool, amount: u64| {
+            accrue_spl_yield(position, pool);
+            position.amount += amount;
+            pool.enrolled_points += amount;
+            position.reward_debt = position.amount as u128 * pool.acc_reward_per_point / ACC_PRECISION;
+        };
+
+        // The early position takes what sat in the vault and the first deposit, the late one only the second
+        enter(&mut early, &mut pool, 300);
+        accumulate(&mut pool.acc_reward_per_point, &mut pool.unallocated, pool.enrolled_points as u128, 1_000).unwrap();
+        enter(&mut late, &mut pool, 100);
+        accumulate(&mut pool.acc_reward_per_point, &mut pool.unallocated, pool.enrolled_points as u128, 400).unwrap();
+        accrue_spl_yield(&mut early, &pool);
+        accrue_spl_yield(&mut late, &pool);
+        assert_eq!(early.pending_tokens, 1_500 + 300);
+        assert_eq!(late.pending_tokens, 100);
+
+        // Redeeming half the points takes half the yield, the rest stays with the other half
+        assert_eq!(redemption_share(early.pending_tokens, early.amount, 150).unwrap(), 900);
+        assert_eq!(redemption_share(early.pending_tokens, early.amount, 300).unwrap(), 1_800);
+        assert!(redemption_share(late.pending_tokens, late.amount, 101).is_err());
+    }
+
+    #[test]
+    fn season_standings_rank_players_by_their_season_points() {
+        let mut season = Season { season_id: 3, end_slot: 100, ..Default::default() };
+        let mut users: Vec<(Pubkey, User)> = (0..=MAX_SEASON_STANDINGS).map(|_| (Pubkey::new_unique(), User::default())).collect();
+        for (points, (player, user)) in users.iter_mut().enumerate() {
+            record_season_win(&mut season, user, *player, 10, points as u64 + 10);
+        }
+        // The weakest player fell out of the index, but keeps their points on their profile
+        let (dropped, dropped_user) = &mut users[0];
+        assert_eq!(season.standings.len(), MAX_SEASON_STANDINGS);
+        assert!(season.standings.iter().all(|entry| entry.player != *dropped));
+        assert_eq!((dropped_user.season_id, dropped_user.season_points), (3, 10));
+
+        // Their next win ranks them by their whole season total
+        record_season_win(&mut season, dropped_user, *dropped, 20, 100);
+        assert_eq!(season.standings[0].player, *dropped);
+        assert_eq!(season.standings[0].won_points, 110);
+    }
+
+    #[test]
+    fn late_season_wins_count_towards_the_next_season() {
+        let mut season = Season { season_id: 3, end_slot: 100, ..Default::default() };
+        let player = Pubkey::new_unique();
+        let mut user = User::default();
+        record_season_win(&mut season, &mut user, player, 50, 7);
+        record_season_win(&mut season, &mut user, player, 100, 5);
+
+        assert_eq!(season.standings[0].won_points, 7);
+        assert_eq!(season.late_standings[0].won_points, 5);
+        assert_eq!((user.season_id, user.season_points), (4, 5));
+    }
+}
@@ -0,0 +1,152 @@
+import * as anchor from '@coral-xyz/anchor';
+import { assert } from 'chai';
+import { Keypair, PublicKey, SystemProgram, Transaction, TransactionInstruction } from '@solana/web3.js';
+import { fundPlayer, winProofRound } from './helpers';
+
+const TOKEN_2022_PROGRAM_ID = new PublicKey("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+const ASSOCIATED_TOKEN_PROGRAM_ID = new PublicKey("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+// Token-2022 mints pad the base layout to an account's 165 bytes, then store the account type and TLV extensions
+const EXTENSIONS_OFFSET = 166;
+const NON_TRANSFERABLE_EXTENSION = 9;
+
+const ACHIEVEMENTS = ["first prime", "first SuperPrime", "10-win streak", "twin prime", "100 rounds"];
+
+describe('prime_slot_checker_achievements', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PrimeSlotChecker as anchor.Program<any>;
+  // A fresh player with a proof win, so the first prime is unlocked and its badge not yet minted
+  const player = Keypair.generate();
+  const payer = player.publicKey;
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+  const userPda = pda(Buffer.from("user"), payer.toBuffer());
+  const badgeMintPda = (achievement: number) => pda(Buffer.from("badge"), payer.toBuffer(), Buffer.from([achievement]));
+  const badgeAccount = (mint: PublicKey, owner: PublicKey = payer) => PublicKey.findProgramAddressSync(
+    [owner.toBuffer(), TOKEN_2022_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+    ASSOCIATED_TOKEN_PROGRAM_ID,
+  )[0];
+
+  // Extension types present in a Token-2022 mint's TLV data: u16 type, u16 length, value
+  const extensionTypes = (data: Buffer) => {
+    const types: number[] = [];
+    for (let offset = EXTENSIONS_OFFSET; offset + 4 <= data.length; offset += 4 + data.readUInt16LE(offset + 2)) {
+      types.push(data.readUInt16LE(offset));
+    }
+    return types;
+  };
+
+  const mintBadge = (achievement: number) => {
+    const badgeMint = badgeMintPda(achievement);
+    return program.methods
+      .mintBadge(achievement)
+      .accounts({
+        user: userPda,
+        badgeMint,
+        badgeAccount: badgeAccount(badgeMint),
+        mintAuthority: pda(Buffer.from("mint_authority")),
+        payer,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([player])
+      .rpc();
+  };
+
+  const unlocked = (user: any, achievement: number) => (user.achievements & (1 << achievement)) !== 0;
+
+  before(async () => {
+    await fundPlayer(program, player);
+    await winProofRound(program, player);
+  });
+
+  it('Unlocks achievements that match the profile counters', async () => {
+    const user = await program.account.user.fetch(userPda);
+    assert.isAbove(user.roundsPlayed.toNumber(), 0);
+    assert.isTrue(unlocked(user, 0));
+    assert.equal(unlocked(user, 0), user.wins.toNumber() > 0);
+    assert.equal(unlocked(user, 1), user.superprimes.toNumber() > 0);
+    assert.equal(unlocked(user, 2), user.bestStreak.toNumber() >= 10);
+    assert.equal(unlocked(user, 4), user.roundsPlayed.toNumber() >= 100);
+    assert.equal(user.badgesMinted & ~user.achievements, 0);
+    console.log('Unlocked:', ACHIEVEMENTS.filter((_, i) => unlocked(user, i)));
+  });
+
+  it('Refuses badges for locked or unknown achievements', async () => {
+    const user = await program.account.user.fetch(userPda);
+    const locked = ACHIEVEMENTS.findIndex((_, i) => !unlocked(user, i));
+    for (const [achievement, error] of [[locked, "AchievementLocked"], [ACHIEVEMENTS.length, "InvalidAchievement"]] as const) {
+      if (achievement < 0) continue;
+      try {
+        await mintBadge(achievement);
+        assert.fail(`Badge ${achievement} should be refused`);
+      } catch (err) {
+        assert.include(`${err}`, error);
+      }
+    }
+  });
+
+  it('Mints a single non-transferable badge per unlocked achievement', async () => {
+    const user = await program.account.user.fetch(userPda);
+    const achievement = ACHIEVEMENTS.findIndex((_, i) => unlocked(user, i) && (user.badgesMinted & (1 << i)) === 0);
+    assert.isAtLeast(achievement, 0);
+
+    await mintBadge(achievement);
+    const badgeMint = badgeMintPda(achievement);
+    const balance = await provider.connection.getTokenAccountBalance(badgeAccount(badgeMint));
+    assert.equal(balance.value.amount, "1");
+    const mintInfo = await provider.connection.getAccountInfo(badgeMint);
+    assert.isTrue(mintInfo.owner.equals(TOKEN_2022_PROGRAM_ID));
+    assert.include(extensionTypes(mintInfo.data), NON_TRANSFERABLE_EXTENSION);
+
+    // Hand-built: create the recipient's ATA, then TransferChecked the badge to it
+    const recipient = provider.wallet.publicKey;
+    const amount = Buffer.alloc(8);
+    amount.writeBigUInt64LE(1n);
+    const transfer = new Transaction().add(
+      new TransactionInstruction({
+        programId: ASSOCIATED_TOKEN_PROGRAM_ID,
+        keys: [
+          { pubkey: payer, isSigner: true, isWritable: true },
+          { pubkey: badgeAccount(badgeMint, recipient), isSigner: false, isWritable: true },
+          { pubkey: recipient, isSigner: false, isWritable: false },
+          { pubkey: badgeMint, isSigner: false, isWritable: false },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          { pubkey: TOKEN_2022_PROGRAM_ID, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.from([1]),
+      }),
+      new TransactionInstruction({
+        programId: TOKEN_2022_PROGRAM_ID,
+        keys: [
+          { pubkey: badgeAccount(badgeMint), isSigner: false, isWritable: true },
+          { pubkey: badgeMint, isSigner: false, isWritable: false },
+          { pubkey: badgeAccount(badgeMint, recipient), isSigner: false, isWritable: true },
+          { pubkey: payer, isSigner: true, isWritable: false },
+        ],
+        data: Buffer.concat([Buffer.from([12]), amount, Buffer.from([0])]),
+      }),
+    );
+    try {
+      await provider.sendAndConfirm(transfer, [player]);
+      assert.fail('Badges should not be transferable');
+    } catch (err) {
+      assert.notInclude(`${err}`, 'Badges should not be transferable');
+    }
+    const kept = await provider.connection.getTokenAccountBalance(badgeAccount(badgeMint));
+    assert.equal(kept.value.amount, "1");
+
+    const after = await program.account.user.fetch(userPda);
+    assert.notEqual(after.badgesMinted & (1 << achievement), 0);
+
+    try {
+      await mintBadge(achievement);
+      assert.fail('A badge can only be minted once');
+    } catch (err) {
+      assert.include(`${err}`, "BadgeAlreadyMinted");
+    }
+  });
+});
@@ -0,0 +1,160 @@
+import * as anchor from '@coral-xyz/anchor';
+import { assert } from 'chai';
+import { PublicKey, SystemProgram } from '@solana/web3.js';
+import { ComputeBudgetProgram } from "@solana/web3.js";
+import { buyPoints } from './helpers';
+
+// Mirrors the program's check_slots budget and math_utils::estimate_is_prime_cu
+const MAX_TRANSACTION_CU = 1_400_000;
+const CHECK_SLOTS_CU_BUDGET = MAX_TRANSACTION_CU - 300_000;
+const MUL_MOD_CU = 400;
+const MILLER_RABIN_ROUND_OVERHEAD_CU = 500;
+const IS_PRIME_OVERHEAD_CU = 100;
+const CANDIDATE_STRIDE = 7_918;
+const estimateIsPrimeCu = (bits: number, k: number) =>
+  IS_PRIME_OVERHEAD_CU + k * (MILLER_RABIN_ROUND_OVERHEAD_CU + 3 * bits * MUL_MOD_CU);
+
+describe('prime_slot_checker_check_slots', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PrimeSlotChecker as anchor.Program<any>;
+
+  const pda = (seed: string) =>
+    PublicKey.findProgramAddressSync([Buffer.from(seed)], program.programId)[0];
+
+  const jackpotPda = pda("jackpot");
+  const treasuryPda = pda("treasury");
+  const stakingTreasuryPda = pda("staking_treasury");
+  const totalWonPointsPda = pda("total_won_points");
+  const playerListPda = pda("player_list");
+  const leaderboardPda = pda("leaderboard");
+  const yieldAccumulatorPda = pda("yield_accumulator");
+  const difficultyPda = pda("difficulty");
+  const epochStatePda = pda("epoch_state");
+  let epochPda: PublicKey;
+  let seasonPda: PublicKey;
+  const [userPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("user"), provider.wallet.publicKey.toBuffer()],
+    program.programId
+  );
+  const [vestingPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("vesting"), provider.wallet.publicKey.toBuffer()],
+    program.programId
+  );
+
+  const accounts = () => ({
+    user: userPda,
+    jackpot: jackpotPda,
+    treasury: treasuryPda,
+    stakingTreasury: stakingTreasuryPda,
+    totalWonPoints: totalWonPointsPda,
+    playerList: playerListPda,
+    leaderboard: leaderboardPda,
+    difficulty: difficultyPda,
+    epochState: epochStatePda,
+    epoch: epochPda,
+    yieldAccumulator: yieldAccumulatorPda,
+    vestingConfig: pda("vesting_config"),
+    vesting: vestingPda,
+    feeVault: pda("fee_vault"),
+    gameStats: pda("game_stats"),
+    winHistory: pda("win_history"),
+    seasonState: pda("season_state"),
+    season: seasonPda,
+    payer: provider.wallet.publicKey,
+    systemProgram: SystemProgram.programId,
+  });
+
+  before(async () => {
+    await program.methods
+      .initializeUser(0)
+      .accounts({ user: userPda, payer: provider.wallet.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    const epochState = await program.account.epochState.fetch(epochStatePda);
+    [epochPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("epoch"), epochState.currentEpoch.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const seasonState = await program.account.seasonState.fetch(pda("season_state"));
+    [seasonPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("season"), seasonState.currentSeason.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+  });
+
+  it('Plays a batch of 4 candidates in one transaction', async () => {
+    const before = await program.account.user.fetch(userPda);
+
+    try {
+      const tx = await program.methods
+        .checkSlots(4)
+        .accounts(accounts())
+        .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 })])
+        .rpc();
+
+      console.log("\nBatch transaction signature:", tx);
+    } catch (err) {
+      console.error("Error in 'Plays a batch of 4 candidates in one transaction' test:", err);
+      console.error(`User Account: ${userPda.toBase58()}`);
+      throw new Error("Batch check_slots failed. Exiting tests.");
+    }
+
+    const after = await program.account.user.fetch(userPda);
+    console.log('Points before:', before.points.toNumber(), 'after:', after.points.toNumber());
+    if (after.lastWonSlot.eq(before.lastWonSlot) && before.points.toNumber() - after.points.toNumber() !== 40) {
+      throw new Error("A losing batch of 4 must cost exactly 40 points.");
+    }
+  });
+
+  it('Fits the largest batch the compute estimate allows under the transaction limit', async () => {
+    // Upper bound on the widest candidate: slot + player-list window sum + pubkey and clock terms + nonce stride
+    const playerList = await program.account.playerList.fetch(playerListPda);
+    const slot = await provider.connection.getSlot();
+    const widest = BigInt(slot + 10_000) + BigInt(playerList.windowSum.toString()) + 200_000n + BigInt(255 * CANDIDATE_STRIDE);
+    const n = Math.min(255, Math.floor(CHECK_SLOTS_CU_BUDGET / estimateIsPrimeCu(widest.toString(2).length, 5)));
+    assert.isAbove(n, 1);
+    // A batch of n costs 10n points plus the 10 point reserve
+    while ((await program.account.user.fetch(userPda)).points.toNumber() <= 10 * n) {
+      await buyPoints(program);
+    }
+
+    const signature = await program.methods
+      .checkSlots(n)
+      .accounts(accounts())
+      .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: MAX_TRANSACTION_CU })])
+      .rpc({ commitment: "confirmed" });
+
+    const tx = await provider.connection.getTransaction(signature, { commitment: "confirmed", maxSupportedTransactionVersion: 0 });
+    const consumed = tx.meta.computeUnitsConsumed;
+    console.log(`Batch of ${n} consumed ${consumed} of ${MAX_TRANSACTION_CU} compute units`);
+    assert.isAtMost(consumed, MAX_TRANSACTION_CU);
+  });
+
+  it('Refuses a batch the compute estimate cannot fit', async () => {
+    // Enough points for 255 rounds, so the batch is refused for its size and not the balance
+    while ((await program.account.user.fetch(userPda)).points.toNumber() <= 10 * 255) {
+      await buyPoints(program);
+    }
+    try {
+      await program.methods
+        .checkSlots(255)
+        .accounts(accounts())
+        .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: MAX_TRANSACTION_CU })])
+        .rpc();
+      assert.fail("A batch of 255 should exceed the compute budget");
+    } catch (err) {
+      assert.include(`${err}`, "BatchExceedsComputeBudget");
+    }
+  });
+
+  it('Rejects an empty batch', async () => {
+    try {
+      await program.methods.checkSlots(0).accounts(accounts()).rpc();
+    } catch (err) {
+      console.log("Empty batch rejected as expected");
+      return;
+    }
+    throw new Error("check_slots(0) should have failed.");
+  });
+});
@@ -10,6 +10,8 @@ describe('prime_slot_checker', () => {
   let userPda, userBump;
   let totalWonPointsPda, totalWonPointsBump;
   let stakingTreasuryPda, stakingTreasuryBump;
+  let yieldAccumulatorPda;
+  let gameStatsPda;
 
   before(async () => {
     [userPda, userBump] = await PublicKey.findProgramAddress(
@@ -27,6 +29,16 @@ describe('prime_slot_checker', () => {
       program.programId
     );
 
+    [yieldAccumulatorPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("yield_accumulator")],
+      program.programId
+    );
+
+    [gameStatsPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("game_stats")],
+      program.programId
+    );
+
     // Ensure the user account is initialized
     try {
       const userAccount = await program.account.user.fetch(userPda);
@@ -102,6 +114,11 @@ describe('prime_slot_checker', () => {
           user: userPda,
           totalWonPoints: totalWonPointsPda,
           stakingTreasury: stakingTreasuryPda,
+          yieldAccumulator: yieldAccumulatorPda,
+          gameStats: gameStatsPda,
+          pointsMint: null,
+          userPointsAccount: null,
+          tokenProgram: null,
           payer: provider.wallet.publicKey,
           systemProgram: SystemProgram.programId,
         })
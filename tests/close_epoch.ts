@@ -0,0 +1,62 @@
+import * as anchor from '@coral-xyz/anchor';
+import { PublicKey, SystemProgram } from '@solana/web3.js';
+
+describe('prime_slot_checker_close_epoch', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PrimeSlotChecker as anchor.Program<any>;
+
+  const pda = (seed: string) =>
+    PublicKey.findProgramAddressSync([Buffer.from(seed)], program.programId)[0];
+  const epochPda = (id: anchor.BN) =>
+    PublicKey.findProgramAddressSync([Buffer.from("epoch"), id.toArrayLike(Buffer, "le", 8)], program.programId)[0];
+
+  const epochStatePda = pda("epoch_state");
+  const treasuryPda = pda("treasury");
+
+  it('Closes the current epoch once its end slot has passed', async () => {
+    const epochState = await program.account.epochState.fetch(epochStatePda);
+    const currentId = epochState.currentEpoch;
+    const epoch = await program.account.epoch.fetch(epochPda(currentId));
+    const slot = await provider.connection.getSlot();
+
+    if (slot < epoch.endSlot.toNumber()) {
+      console.log(`Epoch ${currentId} is open until slot ${epoch.endSlot}, nothing to crank.`);
+      return;
+    }
+
+    try {
+      const tx = await program.methods
+        .closeEpoch()
+        .accounts({
+          epochState: epochStatePda,
+          epoch: epochPda(currentId),
+          nextEpoch: epochPda(currentId.addn(1)),
+          treasury: treasuryPda,
+          gameStats: pda("game_stats"),
+          payer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(epoch.winners.map((w: any) => ({ pubkey: w.winner, isSigner: false, isWritable: true })))
+        .rpc();
+
+      console.log("Close epoch transaction signature:", tx);
+    } catch (err) {
+      console.error("Error in 'Closes the current epoch once its end slot has passed' test:", err);
+      throw new Error("close_epoch failed. Exiting tests.");
+    }
+
+    const archived = await program.account.epoch.fetch(epochPda(currentId));
+    console.log('Archived epoch:', {
+      epochId: archived.epochId.toString(),
+      rounds: archived.rounds.toString(),
+      pot: archived.pot.toString(),
+      rolledForward: archived.rolledForward.toString(),
+      winners: archived.winners.map((w: any) => ({ winner: w.winner.toBase58(), payout: w.payout.toString() })),
+    });
+    if (!archived.settled) {
+      throw new Error("Closed epoch must be marked settled.");
+    }
+  });
+});
@@ -0,0 +1,121 @@
+import * as anchor from '@coral-xyz/anchor';
+import { assert } from 'chai';
+import { Keypair, LAMPORTS_PER_SOL, PublicKey, SystemProgram } from '@solana/web3.js';
+import { fundPlayer, winProofRound } from './helpers';
+
+describe('prime_slot_checker_close_user', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PrimeSlotChecker as anchor.Program<any>;
+  const player = Keypair.generate();
+  const winner = Keypair.generate();
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  const userPda = pda(Buffer.from("user"), player.publicKey.toBuffer());
+  const jackpotPda = pda(Buffer.from("jackpot"));
+  const closeAccounts = {
+    user: userPda,
+    jackpot: jackpotPda,
+    totalWonPoints: pda(Buffer.from("total_won_points")),
+    stakingTreasury: pda(Buffer.from("staking_treasury")),
+    yieldAccumulator: pda(Buffer.from("yield_accumulator")),
+    gameStats: pda(Buffer.from("game_stats")),
+    pointsMint: null,
+    userPointsAccount: null,
+    tokenProgram: null,
+    payer: player.publicKey,
+  };
+
+  before(async () => {
+    const airdrop = await provider.connection.requestAirdrop(player.publicKey, 2 * LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdrop);
+    await program.methods
+      .initializeUser(0)
+      .accounts({ user: userPda, payer: player.publicKey, systemProgram: SystemProgram.programId })
+      .signers([player])
+      .rpc();
+
+    // A second player who bought points and won a round
+    await fundPlayer(program, winner);
+    await winProofRound(program, winner);
+  });
+
+  it('Closes an empty user and returns the rent', async () => {
+    const rent = await provider.connection.getBalance(userPda);
+    const before = await provider.connection.getBalance(player.publicKey);
+
+    await program.methods
+      .closeUser(false, new anchor.BN(0))
+      .accounts(closeAccounts)
+      .signers([player])
+      .rpc();
+
+    assert.isNull(await provider.connection.getAccountInfo(userPda));
+    const after = await provider.connection.getBalance(player.publicKey);
+    console.log(`Reclaimed ${rent} lamports of rent, balance change ${after - before}`);
+    assert.isAbove(after, before);
+  });
+
+  it('Refuses to close a user that still holds points', async () => {
+    await program.methods
+      .initializeUser(0)
+      .accounts({ user: userPda, payer: player.publicKey, systemProgram: SystemProgram.programId })
+      .signers([player])
+      .rpc();
+    await program.methods
+      .payForPoints(new anchor.BN(1000), new anchor.BN(LAMPORTS_PER_SOL))
+      .accounts({
+        user: userPda,
+        treasury: pda(Buffer.from("treasury")),
+        stakingTreasury: pda(Buffer.from("staking_treasury")),
+        pricingConfig: pda(Buffer.from("pricing_config")),
+        totalWonPoints: pda(Buffer.from("total_won_points")),
+        yieldAccumulator: pda(Buffer.from("yield_accumulator")),
+        feeVault: pda(Buffer.from("fee_vault")),
+        gameStats: pda(Buffer.from("game_stats")),
+        seasonState: pda(Buffer.from("season_state")),
+        referrerStats: null,
+        referralVault: null,
+        payer: player.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([player])
+      .rpc();
+
+    try {
+      await program.methods
+        .closeUser(false, new anchor.BN(0))
+        .accounts(closeAccounts)
+        .signers([player])
+        .rpc();
+      assert.fail("User with bought points should not close without force");
+    } catch (err) {
+      assert.include(`${err}`, "UserNotEmpty");
+    }
+  });
+
+  it('Force-closes a user who has played, redeeming won points and forfeiting the rest to the jackpot', async () => {
+    const winnerUserPda = pda(Buffer.from("user"), winner.publicKey.toBuffer());
+    const user = await program.account.user.fetch(winnerUserPda);
+    const jackpot = await program.account.jackpot.fetch(jackpotPda);
+    const totalWonPoints = await program.account.totalWonPoints.fetch(pda(Buffer.from("total_won_points")));
+    assert.isAbove(user.roundsPlayed.toNumber(), 0);
+    assert.isAbove(user.wonPoints.toNumber(), 0);
+
+    await program.methods
+      .closeUser(true, new anchor.BN(0))
+      .accounts({ ...closeAccounts, user: winnerUserPda, payer: winner.publicKey })
+      .signers([winner])
+      .rpc();
+
+    assert.isNull(await provider.connection.getAccountInfo(winnerUserPda));
+    // Won points are redeemed first, only the bought remainder is forfeited
+    const forfeited = user.points.sub(user.wonPoints);
+    const jackpotAfter = await program.account.jackpot.fetch(jackpotPda);
+    const totalAfter = await program.account.totalWonPoints.fetch(pda(Buffer.from("total_won_points")));
+    assert.equal(jackpotAfter.amount.toString(), jackpot.amount.add(forfeited).toString());
+    assert.equal(totalAfter.points.toString(), totalWonPoints.points.sub(user.wonPoints).toString());
+  });
+});
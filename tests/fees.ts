@@ -0,0 +1,54 @@
+import * as anchor from '@coral-xyz/anchor';
+import { assert } from 'chai';
+import { Keypair, PublicKey } from '@solana/web3.js';
+import { buyPoints } from './helpers';
+
+describe('prime_slot_checker_fees', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PrimeSlotChecker as anchor.Program<any>;
+  const payer = provider.wallet.publicKey;
+
+  const [feeVaultPda] = PublicKey.findProgramAddressSync([Buffer.from("fee_vault")], program.programId);
+
+  before(async () => {
+    // A purchase pays the purchase fee, so there is always something to withdraw
+    await buyPoints(program);
+  });
+
+  it('Rejects fee withdrawals from anyone but the admin', async () => {
+    const stranger = Keypair.generate();
+    try {
+      await program.methods
+        .withdrawFees(new anchor.BN(1))
+        .accounts({ feeVault: feeVaultPda, admin: stranger.publicKey, recipient: stranger.publicKey })
+        .signers([stranger])
+        .rpc();
+      assert.fail("Non-admin withdrawal should have been rejected");
+    } catch (err) {
+      assert.include(`${err}`, "Unauthorized");
+    }
+  });
+
+  it('Admin withdraws collected fees', async () => {
+    // The fee vault is initialized by the upgrade authority, the test wallet
+    const vault = await program.account.feeVault.fetch(feeVaultPda);
+    assert.isTrue(vault.admin.equals(payer));
+    console.log('Fees collected:', {
+      purchases: vault.collectedFromPurchases.toString(),
+      wins: vault.collectedFromWins.toString(),
+      withdrawn: vault.withdrawn.toString(),
+    });
+
+    const outstanding = vault.collectedFromPurchases.add(vault.collectedFromWins).sub(vault.withdrawn);
+    assert.isFalse(outstanding.isZero());
+    await program.methods
+      .withdrawFees(outstanding)
+      .accounts({ feeVault: feeVaultPda, admin: payer, recipient: payer })
+      .rpc();
+
+    const updated = await program.account.feeVault.fetch(feeVaultPda);
+    assert.equal(updated.withdrawn.toString(), vault.withdrawn.add(outstanding).toString());
+  });
+});
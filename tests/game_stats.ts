@@ -0,0 +1,74 @@
+import * as anchor from '@coral-xyz/anchor';
+import { assert } from 'chai';
+import { PublicKey, SystemProgram, LAMPORTS_PER_SOL } from '@solana/web3.js';
+
+describe('prime_slot_checker_game_stats', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PrimeSlotChecker as anchor.Program<any>;
+  const payer = provider.wallet.publicKey;
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+  const gameStatsPda = pda(Buffer.from("game_stats"));
+
+  const getStats = () =>
+    program.methods
+      .getStats()
+      .accounts({
+        gameStats: gameStatsPda,
+        treasury: pda(Buffer.from("treasury")),
+        stakingTreasury: pda(Buffer.from("staking_treasury")),
+      })
+      .view();
+
+  it('Returns a snapshot matching the GameStats account', async () => {
+    const snapshot = await getStats();
+    const account = await program.account.gameStats.fetch(gameStatsPda);
+
+    assert.equal(snapshot.totalRounds.toString(), account.totalRounds.toString());
+    assert.equal(snapshot.totalWins.toString(), account.totalWins.toString());
+    assert.equal(snapshot.totalPaidOut.toString(), account.totalPaidOut.toString());
+    assert.isTrue(snapshot.totalWins.gte(snapshot.totalSuperprimes));
+    assert.isTrue(snapshot.totalRounds.gte(snapshot.totalWins));
+
+    const treasuryBalance = await provider.connection.getBalance(pda(Buffer.from("treasury")));
+    assert.equal(snapshot.treasuryBalance.toNumber(), treasuryBalance);
+    console.log('Stats at slot', snapshot.slot.toString(), {
+      rounds: snapshot.totalRounds.toString(),
+      wins: snapshot.totalWins.toString(),
+      deposited: snapshot.totalDeposited.toString(),
+      paidOut: snapshot.totalPaidOut.toString(),
+      yieldClaimed: snapshot.totalYieldClaimed.toString(),
+    });
+  });
+
+  it('Counts point purchases as deposits', async () => {
+    const before = await getStats();
+    const payerBalance = await provider.connection.getBalance(payer);
+
+    await program.methods
+      .payForPoints(new anchor.BN(1000), new anchor.BN(LAMPORTS_PER_SOL))
+      .accounts({
+        user: pda(Buffer.from("user"), payer.toBuffer()),
+        treasury: pda(Buffer.from("treasury")),
+        stakingTreasury: pda(Buffer.from("staking_treasury")),
+        pricingConfig: pda(Buffer.from("pricing_config")),
+        totalWonPoints: pda(Buffer.from("total_won_points")),
+        yieldAccumulator: pda(Buffer.from("yield_accumulator")),
+        feeVault: pda(Buffer.from("fee_vault")),
+        gameStats: gameStatsPda,
+        seasonState: pda(Buffer.from("season_state")),
+        referrerStats: null,
+        referralVault: null,
+        payer,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const after = await getStats();
+    const deposited = after.totalDeposited.sub(before.totalDeposited).toNumber();
+    assert.isAbove(deposited, 0);
+    assert.isAtMost(deposited, payerBalance - await provider.connection.getBalance(payer));
+  });
+});
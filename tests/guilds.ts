@@ -0,0 +1,55 @@
+import * as anchor from '@coral-xyz/anchor';
+import { assert } from 'chai';
+import { Keypair, LAMPORTS_PER_SOL, PublicKey, SystemProgram } from '@solana/web3.js';
+
+describe('prime_slot_checker_guilds', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PrimeSlotChecker as anchor.Program<any>;
+  const payer = provider.wallet.publicKey;
+  const member = Keypair.generate();
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  const guildId = new anchor.BN(Date.now());
+  const guildPda = pda(Buffer.from("guild"), guildId.toArrayLike(Buffer, "le", 8));
+  const memberPda = pda(Buffer.from("guild_member"), member.publicKey.toBuffer());
+
+  before(async () => {
+    await program.methods
+      .initializeGuildLeaderboard()
+      .accounts({ guildLeaderboard: pda(Buffer.from("guild_leaderboard")), payer, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    await program.methods
+      .createGuild(guildId, new anchor.BN(9000))
+      .accounts({ guild: guildPda, payer, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    const airdrop = await provider.connection.requestAirdrop(member.publicKey, LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdrop);
+  });
+
+  it('Members join and cannot leave during the cooldown', async () => {
+    await program.methods
+      .joinGuild(guildId)
+      .accounts({ guild: guildPda, guildMember: memberPda, payer: member.publicKey, systemProgram: SystemProgram.programId })
+      .signers([member])
+      .rpc();
+
+    const guild = await program.account.guild.fetch(guildPda);
+    assert.equal(guild.memberCount.toNumber(), 1);
+
+    try {
+      await program.methods
+        .leaveGuild(guildId)
+        .accounts({ guild: guildPda, guildMember: memberPda, payer: member.publicKey })
+        .signers([member])
+        .rpc();
+      assert.fail("Leaving right after joining should be rejected");
+    } catch (err) {
+      assert.include(`${err}`, "GuildLeaveCooldown");
+    }
+  });
+});
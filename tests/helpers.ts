@@ -0,0 +1,183 @@
+import * as anchor from '@coral-xyz/anchor';
+import { Keypair, LAMPORTS_PER_SOL, PublicKey, SystemProgram, SYSVAR_SLOT_HASHES_PUBKEY } from '@solana/web3.js';
+import { createHash } from 'crypto';
+
+// Shared setup for tests that need a player with won points. Proof-of-search wins are the only
+// deterministic way to win, so these search off-chain for a prime instead of playing random rounds.
+
+const pdaOf = (program: anchor.Program<any>, ...seeds: Buffer[]) =>
+  PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+const programDataOf = (program: anchor.Program<any>) => PublicKey.findProgramAddressSync(
+  [program.programId.toBuffer()],
+  new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+)[0];
+
+function modPow(base: bigint, exp: bigint, mod: bigint): bigint {
+  let result = 1n;
+  base %= mod;
+  while (exp > 0n) {
+    if (exp & 1n) result = (result * base) % mod;
+    exp >>= 1n;
+    base = (base * base) % mod;
+  }
+  return result;
+}
+
+// Deterministic Miller-Rabin, exact for every u64
+function isPrime(n: bigint): boolean {
+  if (n < 4n) return n > 1n;
+  if (n % 2n === 0n) return false;
+  let d = n - 1n;
+  let r = 0;
+  while (d % 2n === 0n) { d /= 2n; r++; }
+  for (const a of [2n, 3n, 5n, 7n, 11n, 13n, 17n, 19n, 23n, 29n, 31n, 37n]) {
+    if (a % n === 0n) continue;
+    let x = modPow(a, d, n);
+    if (x === 1n || x === n - 1n) continue;
+    let composite = true;
+    for (let i = 1; i < r; i++) {
+      x = (x * x) % n;
+      if (x === n - 1n) { composite = false; break; }
+    }
+    if (composite) return false;
+  }
+  return true;
+}
+
+// Same as math_utils::meets_difficulty, the splitmix64 finalizer reduced modulo the difficulty
+const MASK64 = (1n << 64n) - 1n;
+export function meetsDifficulty(n: bigint, difficulty: bigint): boolean {
+  let z = n;
+  z = ((z ^ (z >> 30n)) * 0xbf58476d1ce4e5b9n) & MASK64;
+  z = ((z ^ (z >> 27n)) * 0x94d049bb133111ebn) & MASK64;
+  z = z ^ (z >> 31n);
+  return difficulty <= 1n || z % difficulty === 0n;
+}
+
+// Same mapping as math_utils::search_candidate
+function searchCandidate(slotHash: Buffer, player: PublicKey, nonce: bigint): bigint {
+  const nonceBytes = Buffer.alloc(8);
+  nonceBytes.writeBigUInt64LE(nonce);
+  return createHash('sha256').update(slotHash).update(player.toBuffer()).update(nonceBytes).digest().readBigUInt64LE(0);
+}
+
+// Airdrop SOL to a fresh player, create their user account and buy them 1000 points
+export async function fundPlayer(program: anchor.Program<any>, player: Keypair): Promise<void> {
+  const provider = program.provider as anchor.AnchorProvider;
+  const airdrop = await provider.connection.requestAirdrop(player.publicKey, 3 * LAMPORTS_PER_SOL);
+  await provider.connection.confirmTransaction(airdrop);
+
+  const user = pdaOf(program, Buffer.from("user"), player.publicKey.toBuffer());
+  await program.methods
+    .initializeUser(0)
+    .accounts({ user, payer: player.publicKey, systemProgram: SystemProgram.programId })
+    .signers([player])
+    .rpc();
+  await buyPoints(program, player);
+}
+
+// Buy 1000 points for `player`, the provider wallet when omitted
+export async function buyPoints(program: anchor.Program<any>, player?: Keypair): Promise<void> {
+  const provider = program.provider as anchor.AnchorProvider;
+  const owner = player ? player.publicKey : provider.wallet.publicKey;
+  await program.methods
+    .payForPoints(new anchor.BN(1000), new anchor.BN(LAMPORTS_PER_SOL))
+    .accounts({
+      user: pdaOf(program, Buffer.from("user"), owner.toBuffer()),
+      treasury: pdaOf(program, Buffer.from("treasury")),
+      stakingTreasury: pdaOf(program, Buffer.from("staking_treasury")),
+      pricingConfig: pdaOf(program, Buffer.from("pricing_config")),
+      totalWonPoints: pdaOf(program, Buffer.from("total_won_points")),
+      yieldAccumulator: pdaOf(program, Buffer.from("yield_accumulator")),
+      feeVault: pdaOf(program, Buffer.from("fee_vault")),
+      gameStats: pdaOf(program, Buffer.from("game_stats")),
+      seasonState: pdaOf(program, Buffer.from("season_state")),
+      referrerStats: null,
+      referralVault: null,
+      payer: owner,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers(player ? [player] : [])
+    .rpc();
+}
+
+// Win one proof-of-search round for `player`, the provider wallet when omitted
+export async function winProofRound(program: anchor.Program<any>, player?: Keypair): Promise<void> {
+  const provider = program.provider as anchor.AnchorProvider;
+  const owner = player ? player.publicKey : provider.wallet.publicKey;
+  const searchConfigPda = pdaOf(program, Buffer.from("search_config"));
+  const searchDifficultyPda = pdaOf(program, Buffer.from("search_difficulty"));
+  const searchStatePda = pdaOf(program, Buffer.from("search_state"), owner.toBuffer());
+
+  if (!(await provider.connection.getAccountInfo(searchConfigPda))) {
+    await program.methods
+      .initializeSearchConfig(1, new anchor.BN(3), new anchor.BN(20), new anchor.BN(10))
+      .accounts({
+        searchConfig: searchConfigPda,
+        searchDifficulty: searchDifficultyPda,
+        program: program.programId,
+        programData: programDataOf(program),
+        payer: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+  const config = await program.account.searchConfig.fetch(searchConfigPda);
+  const modulus = 10n ** BigInt(config.suffixDigits);
+  const suffix = BigInt(config.suffix.toString());
+  const difficulty = BigInt((await program.account.difficulty.fetch(searchDifficultyPda)).value.toString());
+
+  // One proof per slot hash, wait for a hash newer than the player's last proof
+  const lastProofSlot = (await provider.connection.getAccountInfo(searchStatePda))
+    ? BigInt((await program.account.searchState.fetch(searchStatePda)).lastProofSlot.toString())
+    : -1n;
+  let slot: bigint;
+  let slotHash: Buffer;
+  while (true) {
+    // Newest SlotHashes entry: u64 length, then (u64 slot, 32 byte hash) pairs
+    const sysvar = (await provider.connection.getAccountInfo(SYSVAR_SLOT_HASHES_PUBKEY))!;
+    slot = sysvar.data.readBigUInt64LE(8);
+    slotHash = sysvar.data.subarray(16, 48);
+    if (slot > lastProofSlot) break;
+    await new Promise((resolve) => setTimeout(resolve, 400));
+  }
+
+  let nonce = 0n;
+  while (true) {
+    const candidate = searchCandidate(slotHash, owner, nonce);
+    if (candidate % modulus === suffix && meetsDifficulty(candidate, difficulty) && isPrime(candidate)) break;
+    nonce++;
+  }
+
+  const seasonState = await program.account.seasonState.fetch(pdaOf(program, Buffer.from("season_state")));
+  const epochState = await program.account.epochState.fetch(pdaOf(program, Buffer.from("epoch_state")));
+  await program.methods
+    .submitPrimeProof(new anchor.BN(slot.toString()), new anchor.BN(nonce.toString()))
+    .accounts({
+      user: pdaOf(program, Buffer.from("user"), owner.toBuffer()),
+      searchState: searchStatePda,
+      searchConfig: searchConfigPda,
+      jackpot: pdaOf(program, Buffer.from("jackpot")),
+      totalWonPoints: pdaOf(program, Buffer.from("total_won_points")),
+      treasury: pdaOf(program, Buffer.from("treasury")),
+      playerList: pdaOf(program, Buffer.from("player_list")),
+      stakingTreasury: pdaOf(program, Buffer.from("staking_treasury")),
+      yieldAccumulator: pdaOf(program, Buffer.from("yield_accumulator")),
+      vestingConfig: pdaOf(program, Buffer.from("vesting_config")),
+      vesting: pdaOf(program, Buffer.from("vesting"), owner.toBuffer()),
+      feeVault: pdaOf(program, Buffer.from("fee_vault")),
+      gameStats: pdaOf(program, Buffer.from("game_stats")),
+      winHistory: pdaOf(program, Buffer.from("win_history")),
+      seasonState: pdaOf(program, Buffer.from("season_state")),
+      season: pdaOf(program, Buffer.from("season"), seasonState.currentSeason.toArrayLike(Buffer, "le", 8)),
+      searchDifficulty: searchDifficultyPda,
+      epochState: pdaOf(program, Buffer.from("epoch_state")),
+      epoch: pdaOf(program, Buffer.from("epoch"), epochState.currentEpoch.toArrayLike(Buffer, "le", 8)),
+      slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+      payer: owner,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers(player ? [player] : [])
+    .rpc();
+}
@@ -21,6 +21,17 @@ describe('prime_slot_checker_account_check', () => {
   let userBump: number;
   let totalWonPointsPda: PublicKey;
   let totalWonPointsBump: number;
+  let difficultyPda: PublicKey;
+  let epochStatePda: PublicKey;
+  let seasonStatePda: PublicKey;
+  let pricingConfigPda: PublicKey;
+  let yieldAccumulatorPda: PublicKey;
+  let vestingConfigPda: PublicKey;
+  let vestingPda: PublicKey;
+  let feeVaultPda: PublicKey;
+  let gameStatsPda: PublicKey;
+  let winHistoryPda: PublicKey;
+  let programDataPda: PublicKey;
 
   before(async () => {
     [jackpotPda, jackpotBump] = await PublicKey.findProgramAddress(
@@ -58,6 +69,62 @@ describe('prime_slot_checker_account_check', () => {
       program.programId
     );
 
+    [difficultyPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("difficulty")],
+      program.programId
+    );
+
+    [epochStatePda] = await PublicKey.findProgramAddress(
+      [Buffer.from("epoch_state")],
+      program.programId
+    );
+
+    [seasonStatePda] = await PublicKey.findProgramAddress(
+      [Buffer.from("season_state")],
+      program.programId
+    );
+
+    [pricingConfigPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("pricing_config")],
+      program.programId
+    );
+
+    [yieldAccumulatorPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("yield_accumulator")],
+      program.programId
+    );
+
+    [vestingConfigPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("vesting_config")],
+      program.programId
+    );
+
+    [vestingPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("vesting"), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+
+    [feeVaultPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("fee_vault")],
+      program.programId
+    );
+
+    [gameStatsPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("game_stats")],
+      program.programId
+    );
+
+    [winHistoryPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("win_history")],
+      program.programId
+    );
+
+    // Config initializers check the signer against the upgrade authority stored here
+    [programDataPda] = await PublicKey.findProgramAddress(
+      [program.programId.toBuffer()],
+      new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+
     try {
       const jackpotAccount = await program.account.jackpot.fetch(jackpotPda);
       console.log("Jackpot Account:", {
@@ -77,6 +144,8 @@ describe('prime_slot_checker_account_check', () => {
           playerList: playerListPda,
           leaderboard: leaderboardPda,
           stakingTreasury: stakingTreasuryPda,
+          program: program.programId,
+          programData: programDataPda,
           payer: provider.wallet.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -101,6 +170,8 @@ describe('prime_slot_checker_account_check', () => {
           playerList: playerListPda,
           leaderboard: leaderboardPda,
           stakingTreasury: stakingTreasuryPda,
+          program: program.programId,
+          programData: programDataPda,
           payer: provider.wallet.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -112,7 +183,9 @@ describe('prime_slot_checker_account_check', () => {
       console.log("Player List Account:", {
         publicKey: playerListPda.toBase58(),
         content: {
-          players: playerListAccount.players.map(player => player.toBase58()),
+          window: playerListAccount.window,
+          windowSum: playerListAccount.windowSum.toString(),
+          players: playerListAccount.entries.slice(0, playerListAccount.count).map(entry => entry.player.toBase58()),
         },
       });
     } catch (err) {
@@ -125,6 +198,8 @@ describe('prime_slot_checker_account_check', () => {
           playerList: playerListPda,
           leaderboard: leaderboardPda,
           stakingTreasury: stakingTreasuryPda,
+          program: program.programId,
+          programData: programDataPda,
           payer: provider.wallet.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -214,6 +289,234 @@ describe('prime_slot_checker_account_check', () => {
         })
         .rpc();
     }
+
+    try {
+      const difficultyAccount = await program.account.difficulty.fetch(difficultyPda);
+      console.log("Difficulty Account:", {
+        publicKey: difficultyPda.toBase58(),
+        content: {
+          value: difficultyAccount.value.toString(),
+          targetWinInterval: difficultyAccount.targetWinInterval.toString(),
+        },
+      });
+    } catch (err) {
+      console.log(`Difficulty Account (${difficultyPda.toBase58()}) does not exist. Initializing...`);
+      // Aim for one win every 100 slots, retargeting every 10 wins
+      await program.methods
+        .initializeDifficulty(new anchor.BN(100), new anchor.BN(10))
+        .accounts({
+          difficulty: difficultyPda,
+          program: program.programId,
+          programData: programDataPda,
+          payer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    try {
+      const epochStateAccount = await program.account.epochState.fetch(epochStatePda);
+      console.log("Epoch State Account:", {
+        publicKey: epochStatePda.toBase58(),
+        content: {
+          currentEpoch: epochStateAccount.currentEpoch.toString(),
+          epochLength: epochStateAccount.epochLength.toString(),
+        },
+      });
+    } catch (err) {
+      console.log(`Epoch State Account (${epochStatePda.toBase58()}) does not exist. Initializing...`);
+      const [epochPda] = await PublicKey.findProgramAddress(
+        [Buffer.from("epoch"), new anchor.BN(0).toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      // Roughly hourly epochs drawing 10% of the treasury
+      await program.methods
+        .initializeEpochs(new anchor.BN(9000), 1000)
+        .accounts({
+          epochState: epochStatePda,
+          epoch: epochPda,
+          program: program.programId,
+          programData: programDataPda,
+          payer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    try {
+      const seasonStateAccount = await program.account.seasonState.fetch(seasonStatePda);
+      console.log("Season State Account:", {
+        publicKey: seasonStatePda.toBase58(),
+        content: {
+          currentSeason: seasonStateAccount.currentSeason.toString(),
+          seasonLength: seasonStateAccount.seasonLength.toString(),
+          pool: seasonStateAccount.pool.toString(),
+        },
+      });
+    } catch (err) {
+      console.log(`Season State Account (${seasonStatePda.toBase58()}) does not exist. Initializing...`);
+      const [seasonPda] = await PublicKey.findProgramAddress(
+        [Buffer.from("season"), new anchor.BN(0).toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      // Roughly weekly seasons funded by 5% of purchases, paying the top five 40/25/15/12/8
+      await program.methods
+        .initializeSeasons(new anchor.BN(1_512_000), 500, [4000, 2500, 1500, 1200, 800])
+        .accounts({
+          seasonState: seasonStatePda,
+          season: seasonPda,
+          program: program.programId,
+          programData: programDataPda,
+          payer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    try {
+      const pricingConfigAccount = await program.account.pricingConfig.fetch(pricingConfigPda);
+      console.log("Pricing Config Account:", {
+        publicKey: pricingConfigPda.toBase58(),
+        content: {
+          lamportsPer1000: pricingConfigAccount.lamportsPer1000.toString(),
+          minQuantity: pricingConfigAccount.minQuantity.toString(),
+          maxQuantity: pricingConfigAccount.maxQuantity.toString(),
+        },
+      });
+    } catch (err) {
+      console.log(`Pricing Config Account (${pricingConfigPda.toBase58()}) does not exist. Initializing...`);
+      await program.methods
+        .initializePricingConfig()
+        .accounts({
+          pricingConfig: pricingConfigPda,
+          program: program.programId,
+          programData: programDataPda,
+          payer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    try {
+      const yieldAccumulatorAccount = await program.account.yieldAccumulator.fetch(yieldAccumulatorPda);
+      console.log("Yield Accumulator Account:", {
+        publicKey: yieldAccumulatorPda.toBase58(),
+        content: {
+          accRewardPerPoint: yieldAccumulatorAccount.accRewardPerPoint.toString(),
+          unallocated: yieldAccumulatorAccount.unallocated.toString(),
+        },
+      });
+    } catch (err) {
+      console.log(`Yield Accumulator Account (${yieldAccumulatorPda.toBase58()}) does not exist. Initializing...`);
+      await program.methods
+        .initializeYieldAccumulator()
+        .accounts({
+          yieldAccumulator: yieldAccumulatorPda,
+          stakingTreasury: stakingTreasuryPda,
+          payer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    try {
+      const vestingConfigAccount = await program.account.vestingConfig.fetch(vestingConfigPda);
+      console.log("Vesting Config Account:", {
+        publicKey: vestingConfigPda.toBase58(),
+        content: {
+          thresholdLamports: vestingConfigAccount.thresholdLamports.toString(),
+          immediateBps: vestingConfigAccount.immediateBps,
+          vestingSlots: vestingConfigAccount.vestingSlots.toString(),
+        },
+      });
+    } catch (err) {
+      console.log(`Vesting Config Account (${vestingConfigPda.toBase58()}) does not exist. Initializing...`);
+      // Wins above 1 SOL pay 25% now and vest over ~1 day
+      await program.methods
+        .initializeVestingConfig(new anchor.BN(1_000_000_000), 2500, new anchor.BN(216_000))
+        .accounts({
+          vestingConfig: vestingConfigPda,
+          program: program.programId,
+          programData: programDataPda,
+          payer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    try {
+      const feeVaultAccount = await program.account.feeVault.fetch(feeVaultPda);
+      console.log("Fee Vault Account:", {
+        publicKey: feeVaultPda.toBase58(),
+        content: {
+          purchaseFeeBps: feeVaultAccount.purchaseFeeBps,
+          winFeeBps: feeVaultAccount.winFeeBps,
+        },
+      });
+    } catch (err) {
+      console.log(`Fee Vault Account (${feeVaultPda.toBase58()}) does not exist. Initializing...`);
+      await program.methods
+        .initializeFeeVault(200, 100)
+        .accounts({
+          feeVault: feeVaultPda,
+          program: program.programId,
+          programData: programDataPda,
+          payer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    try {
+      const gameStatsAccount = await program.account.gameStats.fetch(gameStatsPda);
+      console.log("Game Stats Account:", {
+        publicKey: gameStatsPda.toBase58(),
+        content: {
+          totalRounds: gameStatsAccount.totalRounds.toString(),
+          totalWins: gameStatsAccount.totalWins.toString(),
+        },
+      });
+    } catch (err) {
+      console.log(`Game Stats Account (${gameStatsPda.toBase58()}) does not exist. Initializing...`);
+      await program.methods
+        .initializeGameStats()
+        .accounts({
+          gameStats: gameStatsPda,
+          payer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    try {
+      const winHistoryAccount = await program.account.winHistory.fetch(winHistoryPda);
+      console.log("Win History Account:", {
+        publicKey: winHistoryPda.toBase58(),
+        content: {
+          count: winHistoryAccount.count,
+          head: winHistoryAccount.head,
+        },
+      });
+    } catch (err) {
+      console.log(`Win History Account (${winHistoryPda.toBase58()}) does not exist. Initializing...`);
+      await program.methods
+        .initializeWinHistory()
+        .accounts({
+          winHistory: winHistoryPda,
+          payer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    await program.methods
+      .initializeVesting()
+      .accounts({
+        vesting: vestingPda,
+        payer: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
   });
 
   it('Check account initialization', async () => {
@@ -247,7 +550,9 @@ describe('prime_slot_checker_account_check', () => {
       console.log("Player List Account:", {
         publicKey: playerListPda.toBase58(),
         content: {
-          players: playerListAccount.players.map(player => player.toBase58()),
+          window: playerListAccount.window,
+          windowSum: playerListAccount.windowSum.toString(),
+          players: playerListAccount.entries.slice(0, playerListAccount.count).map(entry => entry.player.toBase58()),
         },
       });
     } catch (err) {
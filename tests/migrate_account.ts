@@ -0,0 +1,85 @@
+import * as anchor from '@coral-xyz/anchor';
+import { assert } from 'chai';
+import { PublicKey, SystemProgram } from '@solana/web3.js';
+import { decodeAccount, fetchAccount } from '../app/accounts';
+
+describe('prime_slot_checker_migrate_account', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PrimeSlotChecker as anchor.Program<any>;
+  const payer = provider.wallet.publicKey;
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+  const userPda = pda(Buffer.from("user"), payer.toBuffer());
+
+  it('Reads current accounts at their version and refuses to migrate them again', async () => {
+    const user = await fetchAccount(program, "User", userPda);
+    assert.equal(user.version, 3);
+
+    try {
+      await program.methods
+        .migrateAccount()
+        .accounts({
+          target: userPda,
+          yieldAccumulator: pda(Buffer.from("yield_accumulator")),
+          payer,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("Current layout should not migrate");
+    } catch (err) {
+      assert.include(`${err}`, "AlreadyMigrated");
+    }
+  });
+
+  it('Reads a legacy unversioned user layout', async () => {
+    const current = await program.account.user.fetch(userPda);
+    const legacy = Buffer.alloc(48);
+    (await provider.connection.getAccountInfo(userPda)).data.copy(legacy, 0, 0, 8);
+    legacy.writeBigInt64LE(BigInt(1234), 8);
+    legacy.writeBigInt64LE(BigInt(56), 16);
+
+    const decoded = decodeAccount(program, "User", legacy);
+    assert.equal(decoded.version, 0);
+    assert.equal(decoded.account.points.toNumber(), 1234);
+    assert.equal(decoded.account.wonPoints.toNumber(), 56);
+    assert.isTrue(decoded.account.referrer.equals(PublicKey.default));
+    console.log('Current user points:', current.points.toString());
+  });
+
+  it('Reads a version 1 user without the profile counters', async () => {
+    const current = (await provider.connection.getAccountInfo(userPda)).data;
+    const v1 = Buffer.from(current.subarray(0, 121));
+    v1[8] = 1;
+
+    const decoded = decodeAccount(program, "User", v1);
+    assert.equal(decoded.version, 1);
+    assert.equal(decoded.account.roundsPlayed.toNumber(), 0);
+    assert.equal(decoded.account.points.toString(), (await program.account.user.fetch(userPda)).points.toString());
+  });
+
+  it('Reads a version 2 user without the achievement bitmasks', async () => {
+    const current = await program.account.user.fetch(userPda);
+    const v2 = Buffer.from((await provider.connection.getAccountInfo(userPda)).data.subarray(0, 195));
+    v2[8] = 2;
+
+    const decoded = decodeAccount(program, "User", v2);
+    assert.equal(decoded.version, 2);
+    assert.isTrue(decoded.outdated);
+    assert.equal(decoded.account.achievements, 0);
+    assert.equal(decoded.account.roundsPlayed.toString(), current.roundsPlayed.toString());
+  });
+
+  it('Reads a version 3 user without the season points', async () => {
+    const current = await program.account.user.fetch(userPda);
+    const v3 = Buffer.from((await provider.connection.getAccountInfo(userPda)).data.subarray(0, 203));
+    v3[8] = 3;
+
+    const decoded = decodeAccount(program, "User", v3);
+    assert.equal(decoded.version, 3);
+    assert.isTrue(decoded.outdated);
+    assert.equal(decoded.account.seasonPoints.toNumber(), 0);
+    assert.equal(decoded.account.achievements, current.achievements);
+  });
+});
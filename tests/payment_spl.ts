@@ -0,0 +1,257 @@
+import * as anchor from '@coral-xyz/anchor';
+import { assert } from 'chai';
+import { Keypair, PublicKey, SystemProgram, Transaction, TransactionInstruction } from '@solana/web3.js';
+import { winProofRound } from './helpers';
+
+const TOKEN_PROGRAM_ID = new PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+const ASSOCIATED_TOKEN_PROGRAM_ID = new PublicKey("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+const MINT_SIZE = 82;
+
+describe('prime_slot_checker_payment_spl', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PrimeSlotChecker as anchor.Program<any>;
+  const payer = provider.wallet.publicKey;
+  const mint = Keypair.generate();
+  const pricePer1000 = new anchor.BN(1_000_000);
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+  const ata = (owner: PublicKey) => PublicKey.findProgramAddressSync(
+    [owner.toBuffer(), TOKEN_PROGRAM_ID.toBuffer(), mint.publicKey.toBuffer()],
+    ASSOCIATED_TOKEN_PROGRAM_ID
+  )[0];
+  const balance = async (account: PublicKey) =>
+    BigInt((await provider.connection.getTokenAccountBalance(account)).value.amount);
+
+  const userPda = pda(Buffer.from("user"), payer.toBuffer());
+  const paymentConfigPda = pda(Buffer.from("payment_config"));
+  const treasuryVault = pda(Buffer.from("treasury_vault"), mint.publicKey.toBuffer());
+  const stakingVault = pda(Buffer.from("staking_vault"), mint.publicKey.toBuffer());
+  const feeTokenVault = pda(Buffer.from("fee_token_vault"), mint.publicKey.toBuffer());
+  const splYieldPool = pda(Buffer.from("spl_yield_pool"), mint.publicKey.toBuffer());
+  const splYieldPosition = pda(Buffer.from("spl_yield_position"), mint.publicKey.toBuffer(), payer.toBuffer());
+  const vaultAuthority = pda(Buffer.from("vault_authority"));
+  const payerTokenAccount = ata(payer);
+  // Config initializers check the signer against the upgrade authority stored here
+  const programDataPda = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  )[0];
+
+  const purchase = (quantity: number, maxAmount: anchor.BN) =>
+    program.methods
+      .payForPointsSpl(new anchor.BN(quantity), maxAmount)
+      .accounts({
+        user: userPda,
+        paymentConfig: paymentConfigPda,
+        pricingConfig: pda(Buffer.from("pricing_config")),
+        feeVault: pda(Buffer.from("fee_vault")),
+        mint: mint.publicKey,
+        payerTokenAccount,
+        treasuryVault,
+        stakingVault,
+        feeTokenVault,
+        splYieldPool,
+        referrerStats: null,
+        referralVault: null,
+        referrerTokenAccount: null,
+        payer,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+  before(async () => {
+    await program.methods
+      .initializePaymentConfig(5000)
+      .accounts({ paymentConfig: paymentConfigPda, program: program.programId, programData: programDataPda, payer, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    // Classic SPL mint with 6 decimals, built by hand: InitializeMint2, create ATA, MintTo
+    const mintAmount = Buffer.alloc(8);
+    mintAmount.writeBigUInt64LE(1_000_000_000n);
+    const tx = new Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: payer,
+        newAccountPubkey: mint.publicKey,
+        lamports: await provider.connection.getMinimumBalanceForRentExemption(MINT_SIZE),
+        space: MINT_SIZE,
+        programId: TOKEN_PROGRAM_ID,
+      }),
+      new TransactionInstruction({
+        programId: TOKEN_PROGRAM_ID,
+        keys: [{ pubkey: mint.publicKey, isSigner: false, isWritable: true }],
+        data: Buffer.concat([Buffer.from([20, 6]), payer.toBuffer(), Buffer.from([0])]),
+      }),
+      new TransactionInstruction({
+        programId: ASSOCIATED_TOKEN_PROGRAM_ID,
+        keys: [
+          { pubkey: payer, isSigner: true, isWritable: true },
+          { pubkey: payerTokenAccount, isSigner: false, isWritable: true },
+          { pubkey: payer, isSigner: false, isWritable: false },
+          { pubkey: mint.publicKey, isSigner: false, isWritable: false },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.from([1]),
+      }),
+      new TransactionInstruction({
+        programId: TOKEN_PROGRAM_ID,
+        keys: [
+          { pubkey: mint.publicKey, isSigner: false, isWritable: true },
+          { pubkey: payerTokenAccount, isSigner: false, isWritable: true },
+          { pubkey: payer, isSigner: true, isWritable: false },
+        ],
+        data: Buffer.concat([Buffer.from([7]), mintAmount]),
+      }),
+    );
+    await provider.sendAndConfirm(tx, [mint]);
+
+    await program.methods
+      .setAcceptedMint(pricePer1000)
+      .accounts({
+        paymentConfig: paymentConfigPda,
+        mint: mint.publicKey,
+        treasuryVault,
+        stakingVault,
+        splYieldPool,
+        feeTokenVault,
+        vaultAuthority,
+        admin: payer,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  it('Buys points with an accepted mint and splits the payment across the vaults', async () => {
+    const user = await program.account.user.fetch(userPda);
+    const paid = await balance(payerTokenAccount);
+    const feesBefore = await balance(feeTokenVault);
+    const feeVaultBefore = await program.account.feeVault.fetch(pda(Buffer.from("fee_vault")));
+
+    await purchase(1000, pricePer1000);
+
+    const after = await program.account.user.fetch(userPda);
+    const spent = paid - await balance(payerTokenAccount);
+    const fees = await balance(feeTokenVault);
+    // SPL fees are recorded like SOL ones, counted in the paid mint's units
+    const feeVaultAfter = await program.account.feeVault.fetch(pda(Buffer.from("fee_vault")));
+    assert.equal(feeVaultAfter.collectedFromPurchases.sub(feeVaultBefore.collectedFromPurchases).toString(), (fees - feesBefore).toString());
+    const treasury = await balance(treasuryVault);
+    const staking = await balance(stakingVault);
+    assert.equal(after.points.toNumber(), user.points.toNumber() + 1000);
+    assert.equal(spent.toString(), pricePer1000.toString());
+    assert.equal((fees + treasury + staking).toString(), spent.toString());
+    assert.isTrue(staking > 0n);
+  });
+
+  it('Rejects an SPL purchase priced above max_amount', async () => {
+    try {
+      await purchase(1000, new anchor.BN(1));
+      assert.fail("Purchase with max_amount = 1 should have failed");
+    } catch (err) {
+      assert.include(`${err}`, "SlippageExceeded");
+    }
+  });
+
+  it('Pays won points put behind the mint out of its yield vault, and no SOL for them', async () => {
+    if ((await program.account.user.fetch(userPda)).wonPoints.isZero()) {
+      await winProofRound(program);
+    }
+    const user = await program.account.user.fetch(userPda);
+    const amount = anchor.BN.min(user.wonPoints, user.points);
+    assert.isFalse(amount.isZero());
+    const totalBefore = await program.account.totalWonPoints.fetch(pda(Buffer.from("total_won_points")));
+
+    await program.methods
+      .enterSplYield(amount)
+      .accounts({
+        user: userPda,
+        yieldAccumulator: pda(Buffer.from("yield_accumulator")),
+        totalWonPoints: pda(Buffer.from("total_won_points")),
+        mint: mint.publicKey,
+        splYieldPool,
+        splYieldPosition,
+        payer,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    // Points behind a mint stop sharing the SOL yield
+    const entered = await program.account.user.fetch(userPda);
+    const totalAfter = await program.account.totalWonPoints.fetch(pda(Buffer.from("total_won_points")));
+    assert.equal(entered.wonPoints.toString(), user.wonPoints.sub(amount).toString());
+    assert.equal(totalAfter.points.toString(), totalBefore.points.sub(amount).toString());
+
+    // The only position behind this fresh mint earns its whole yield vault, give or take rounding
+    await purchase(1000, pricePer1000);
+    const vault = await balance(stakingVault);
+    const redeem = (minTokensOut: bigint) =>
+      program.methods
+        .redeemWonPointsSpl(amount, new anchor.BN(minTokensOut.toString()))
+        .accounts({
+          mint: mint.publicKey,
+          splYieldPool,
+          splYieldPosition,
+          stakingVault,
+          userTokenAccount: payerTokenAccount,
+          vaultAuthority,
+          payer,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    try {
+      await redeem(vault + 1n);
+      assert.fail("No redemption can take more than the yield vault holds");
+    } catch (err) {
+      assert.include(`${err}`, "SlippageExceeded");
+    }
+
+    const tokensBefore = await balance(payerTokenAccount);
+    const stakingTreasuryBefore = await provider.connection.getBalance(pda(Buffer.from("staking_treasury")));
+    await redeem(vault - 1n);
+    const received = await balance(payerTokenAccount) - tokensBefore;
+    assert.isTrue(received >= vault - 1n && received <= vault);
+
+    // Paid in the mint only: no lamports left the SOL yield pool and no SOL claim was recorded
+    const after = await program.account.user.fetch(userPda);
+    assert.equal(await provider.connection.getBalance(pda(Buffer.from("staking_treasury"))), stakingTreasuryBefore);
+    assert.equal(after.lamportsClaimed.toString(), entered.lamportsClaimed.toString());
+    const position = await program.account.splYieldPosition.fetch(splYieldPosition);
+    assert.isTrue(position.amount.isZero());
+  });
+
+  it('Lets the admin withdraw the treasury and fee vaults but not the yield vault', async () => {
+    const withdraw = (vault: PublicKey, amount: bigint) =>
+      program.methods
+        .withdrawSplVault(new anchor.BN(amount.toString()))
+        .accounts({
+          paymentConfig: paymentConfigPda,
+          mint: mint.publicKey,
+          vault,
+          recipient: payerTokenAccount,
+          vaultAuthority,
+          admin: payer,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    const treasury = await balance(treasuryVault);
+    const fees = await balance(feeTokenVault);
+    const before = await balance(payerTokenAccount);
+    await withdraw(treasuryVault, treasury);
+    await withdraw(feeTokenVault, fees);
+    assert.equal(await balance(treasuryVault), 0n);
+    assert.equal(await balance(feeTokenVault), 0n);
+    assert.equal((await balance(payerTokenAccount) - before).toString(), (treasury + fees).toString());
+
+    try {
+      await withdraw(stakingVault, 1n);
+      assert.fail("The yield vault belongs to won point holders");
+    } catch (err) {
+      assert.include(`${err}`, "InvalidVault");
+    }
+  });
+});
@@ -1,4 +1,5 @@
 import * as anchor from '@coral-xyz/anchor';
+import { assert } from 'chai';
 import { PublicKey, SystemProgram } from '@solana/web3.js';
 
 describe('prime_slot_checker_payment_test', () => {
@@ -11,6 +12,13 @@ describe('prime_slot_checker_payment_test', () => {
   let userBump: number;
   let treasuryPda: PublicKey;
   let treasuryBump: number;
+  let stakingTreasuryPda: PublicKey;
+  let pricingConfigPda: PublicKey;
+  let totalWonPointsPda: PublicKey;
+  let yieldAccumulatorPda: PublicKey;
+  let feeVaultPda: PublicKey;
+  let gameStatsPda: PublicKey;
+  let seasonStatePda: PublicKey;
 
   before(async () => {
     [treasuryPda, treasuryBump] = await PublicKey.findProgramAddress(
@@ -18,6 +26,41 @@ describe('prime_slot_checker_payment_test', () => {
       program.programId
     );
 
+    [stakingTreasuryPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("staking_treasury")],
+      program.programId
+    );
+
+    [feeVaultPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("fee_vault")],
+      program.programId
+    );
+
+    [gameStatsPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("game_stats")],
+      program.programId
+    );
+
+    [seasonStatePda] = await PublicKey.findProgramAddress(
+      [Buffer.from("season_state")],
+      program.programId
+    );
+
+    [pricingConfigPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("pricing_config")],
+      program.programId
+    );
+
+    [totalWonPointsPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("total_won_points")],
+      program.programId
+    );
+
+    [yieldAccumulatorPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("yield_accumulator")],
+      program.programId
+    );
+
     try {
       [userPda, userBump] = await PublicKey.findProgramAddress(
         [Buffer.from("user"), provider.wallet.publicKey.toBuffer()],
@@ -42,9 +85,19 @@ describe('prime_slot_checker_payment_test', () => {
 
   it('User pays 1 SOL to receive 1000 points', async () => {
     try {
-      const tx = await program.methods.payForPoints(userBump).accounts({
+      // Allow up to 1 SOL so a pricing change can't overcharge us
+      const tx = await program.methods.payForPoints(new anchor.BN(1000), new anchor.BN(1_000_000_000)).accounts({
         user: userPda,
         treasury: treasuryPda,
+        stakingTreasury: stakingTreasuryPda,
+        pricingConfig: pricingConfigPda,
+        totalWonPoints: totalWonPointsPda,
+        yieldAccumulator: yieldAccumulatorPda,
+        feeVault: feeVaultPda,
+        gameStats: gameStatsPda,
+        seasonState: seasonStatePda,
+        referrerStats: null,
+        referralVault: null,
         payer: provider.wallet.publicKey,
         systemProgram: SystemProgram.programId,
       }).rpc();
@@ -63,5 +116,35 @@ describe('prime_slot_checker_payment_test', () => {
       throw new Error("Test 'User pays 1 SOL to receive 1000 points' failed. Exiting tests.");
     }
   });
-});
 
+  it('Rejects a purchase priced above max_lamports', async () => {
+    const userBefore = await program.account.user.fetch(userPda);
+    const lamportsBefore = await provider.connection.getBalance(provider.wallet.publicKey);
+
+    try {
+      await program.methods.payForPoints(new anchor.BN(1000), new anchor.BN(1)).accounts({
+        user: userPda,
+        treasury: treasuryPda,
+        stakingTreasury: stakingTreasuryPda,
+        pricingConfig: pricingConfigPda,
+        totalWonPoints: totalWonPointsPda,
+        yieldAccumulator: yieldAccumulatorPda,
+        feeVault: feeVaultPda,
+        gameStats: gameStatsPda,
+        seasonState: seasonStatePda,
+        referrerStats: null,
+        referralVault: null,
+        payer: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      }).rpc();
+      assert.fail("Purchase with max_lamports = 1 should have failed.");
+    } catch (err) {
+      assert.include(`${err}`, "SlippageExceeded");
+    }
+
+    // Refused in preflight, so nothing was charged and no points were credited
+    const userAfter = await program.account.user.fetch(userPda);
+    assert.equal(userAfter.points.toString(), userBefore.points.toString());
+    assert.equal(await provider.connection.getBalance(provider.wallet.publicKey), lamportsBefore);
+  });
+});
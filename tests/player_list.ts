@@ -0,0 +1,70 @@
+import * as anchor from '@coral-xyz/anchor';
+import { assert } from 'chai';
+import { PublicKey } from '@solana/web3.js';
+import { decodeAccount, pubkeyToNumber, recentPlayers } from '../app/accounts';
+
+describe('prime_slot_checker_player_list', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PrimeSlotChecker as anchor.Program<any>;
+  const admin = provider.wallet.publicKey;
+
+  const playerListPda = PublicKey.findProgramAddressSync([Buffer.from("player_list")], program.programId)[0];
+
+  const auditedSum = (playerList: any) =>
+    recentPlayers(playerList).reduce((sum, entry) => sum + entry.number.toNumber(), 0);
+
+  it('Keeps the window sum equal to the entries inside the window', async () => {
+    const playerList = await program.account.playerList.fetch(playerListPda);
+    assert.equal(playerList.version, 2);
+    assert.equal(auditedSum(playerList), playerList.windowSum.toNumber());
+
+    for (const entry of recentPlayers(playerList)) {
+      assert.equal(entry.number.toNumber(), pubkeyToNumber(entry.player));
+    }
+    console.log('Window', playerList.window, 'of', playerList.count, 'players sums to', playerList.windowSum.toString());
+  });
+
+  it('Resizes the window and recomputes the sum', async () => {
+    // initialize is restricted to the upgrade authority, the test wallet, so it administers the list
+    const before = await program.account.playerList.fetch(playerListPda);
+    assert.isTrue(before.admin.equals(admin));
+
+    await program.methods.setPlayerWindow(3).accounts({ playerList: playerListPda, admin }).rpc();
+    const resized = await program.account.playerList.fetch(playerListPda);
+    assert.equal(resized.window, 3);
+    assert.equal(auditedSum(resized), resized.windowSum.toNumber());
+
+    await program.methods.setPlayerWindow(before.window).accounts({ playerList: playerListPda, admin }).rpc();
+    const restored = await program.account.playerList.fetch(playerListPda);
+    assert.equal(restored.windowSum.toString(), before.windowSum.toString());
+  });
+
+  it('Rejects windows outside the ring buffer', async () => {
+    for (const window of [0, 33]) {
+      try {
+        await program.methods.setPlayerWindow(window).accounts({ playerList: playerListPda, admin }).rpc();
+        assert.fail(`Window ${window} should be rejected`);
+      } catch (err) {
+        assert.include(`${err}`, "InvalidPlayerWindow");
+      }
+    }
+  });
+
+  it('Reads a legacy Vec player list as a ring buffer', async () => {
+    const players = Array.from({ length: 12 }, () => anchor.web3.Keypair.generate().publicKey);
+    const legacy = Buffer.alloc(680);
+    (await provider.connection.getAccountInfo(playerListPda)).data.copy(legacy, 0, 0, 8);
+    legacy.writeUInt32LE(players.length, 8);
+    players.forEach((player, i) => player.toBuffer().copy(legacy, 12 + 32 * i));
+
+    const decoded = decodeAccount(program, "PlayerList", legacy);
+    assert.equal(decoded.version, 0);
+    assert.equal(decoded.account.count, 12);
+    assert.isTrue(recentPlayers(decoded.account)[0].player.equals(players[11]));
+
+    const expected = players.slice(2).reduce((sum, player) => sum + pubkeyToNumber(player), 0);
+    assert.equal(decoded.account.windowSum.toNumber(), expected);
+  });
+});
@@ -0,0 +1,169 @@
+import * as anchor from '@coral-xyz/anchor';
+import { assert } from 'chai';
+import { PublicKey, SystemProgram } from '@solana/web3.js';
+import { ComputeBudgetProgram } from "@solana/web3.js";
+
+const TOKEN_PROGRAM_ID = new PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+const ASSOCIATED_TOKEN_PROGRAM_ID = new PublicKey("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+describe('prime_slot_checker_point_tokens', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PrimeSlotChecker as anchor.Program<any>;
+  const payer = provider.wallet.publicKey;
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  const pointsMintPda = pda(Buffer.from("points_mint"));
+  const mintAuthorityPda = pda(Buffer.from("mint_authority"));
+  const userPda = pda(Buffer.from("user"), payer.toBuffer());
+  const [userPointsAccount] = PublicKey.findProgramAddressSync(
+    [payer.toBuffer(), TOKEN_PROGRAM_ID.toBuffer(), pointsMintPda.toBuffer()],
+    ASSOCIATED_TOKEN_PROGRAM_ID
+  );
+
+  before(async () => {
+    if (await provider.connection.getAccountInfo(pointsMintPda)) {
+      return;
+    }
+    await program.methods
+      .initializePointsMint()
+      .accounts({
+        pointsMint: pointsMintPda,
+        mintAuthority: mintAuthorityPda,
+        payer,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  it('Migrates internal points to POINTS tokens once', async () => {
+    const before = await program.account.user.fetch(userPda);
+
+    await program.methods
+      .migratePointsToTokens()
+      .accounts({
+        user: userPda,
+        tokenMigration: pda(Buffer.from("token_migration"), payer.toBuffer()),
+        pointsMint: pointsMintPda,
+        userPointsAccount,
+        mintAuthority: mintAuthorityPda,
+        payer,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const balance = await provider.connection.getTokenAccountBalance(userPointsAccount);
+    console.log('Migrated', before.points.toString(), 'points, token balance:', balance.value.amount);
+
+    // Only the spendable part becomes tokens, won points stay locked on the account
+    const after = await program.account.user.fetch(userPda);
+    const locked = Math.min(Math.max(before.points.toNumber(), 0), Math.max(before.wonPoints.toNumber(), 0));
+    assert.equal(after.points.toNumber(), locked);
+    assert.equal(after.wonPoints.toString(), before.wonPoints.toString());
+    assert.equal(balance.value.amount, (Math.max(before.points.toNumber(), 0) - locked).toString());
+  });
+
+  it('Buys POINTS tokens and burns them playing a round', async () => {
+    await program.methods
+      .payForPointTokens(new anchor.BN(1000), new anchor.BN(1_000_000_000))
+      .accounts({
+        purchase: {
+          user: userPda,
+          treasury: pda(Buffer.from("treasury")),
+          stakingTreasury: pda(Buffer.from("staking_treasury")),
+          pricingConfig: pda(Buffer.from("pricing_config")),
+          totalWonPoints: pda(Buffer.from("total_won_points")),
+          yieldAccumulator: pda(Buffer.from("yield_accumulator")),
+          feeVault: pda(Buffer.from("fee_vault")),
+          gameStats: pda(Buffer.from("game_stats")),
+          seasonState: pda(Buffer.from("season_state")),
+          referrerStats: null,
+          referralVault: null,
+          payer,
+          systemProgram: SystemProgram.programId,
+        },
+        pointsMint: pointsMintPda,
+        userPointsAccount,
+        mintAuthority: mintAuthorityPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const epochStatePda = pda(Buffer.from("epoch_state"));
+    const epochState = await program.account.epochState.fetch(epochStatePda);
+    const seasonStatePda = pda(Buffer.from("season_state"));
+    const seasonState = await program.account.seasonState.fetch(seasonStatePda);
+    const before = await provider.connection.getTokenAccountBalance(userPointsAccount);
+
+    const tx = await program.methods
+      .checkSlotTokens()
+      .accounts({
+        game: {
+          user: userPda,
+          jackpot: pda(Buffer.from("jackpot")),
+          totalWonPoints: pda(Buffer.from("total_won_points")),
+          treasury: pda(Buffer.from("treasury")),
+          playerList: pda(Buffer.from("player_list")),
+          leaderboard: pda(Buffer.from("leaderboard")),
+          stakingTreasury: pda(Buffer.from("staking_treasury")),
+          difficulty: pda(Buffer.from("difficulty")),
+          epochState: epochStatePda,
+          epoch: pda(Buffer.from("epoch"), epochState.currentEpoch.toArrayLike(Buffer, "le", 8)),
+          yieldAccumulator: pda(Buffer.from("yield_accumulator")),
+          vestingConfig: pda(Buffer.from("vesting_config")),
+          vesting: pda(Buffer.from("vesting"), payer.toBuffer()),
+          feeVault: pda(Buffer.from("fee_vault")),
+          gameStats: pda(Buffer.from("game_stats")),
+          winHistory: pda(Buffer.from("win_history")),
+          seasonState: seasonStatePda,
+          season: pda(Buffer.from("season"), seasonState.currentSeason.toArrayLike(Buffer, "le", 8)),
+          payer,
+          systemProgram: SystemProgram.programId,
+        },
+        pointsMint: pointsMintPda,
+        userPointsAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 300000 })])
+      .rpc();
+
+    const after = await provider.connection.getTokenAccountBalance(userPointsAccount);
+    console.log("Token round transaction signature:", tx);
+    console.log('POINTS before:', before.value.amount, 'after:', after.value.amount);
+    // The round burns its cost, a win is credited to the account and never minted
+    assert.equal((BigInt(before.value.amount) - BigInt(after.value.amount)).toString(), "10");
+  });
+
+  it('Claims won points from the account without touching POINTS tokens', async () => {
+    const before = await program.account.user.fetch(userPda);
+    const tokensBefore = BigInt((await provider.connection.getTokenAccountBalance(userPointsAccount)).value.amount);
+
+    // Won points were never minted, so every one of them is backed by internal points
+    await program.methods
+      .claimLamports(0)
+      .accounts({
+        user: userPda,
+        totalWonPoints: pda(Buffer.from("total_won_points")),
+        stakingTreasury: pda(Buffer.from("staking_treasury")),
+        yieldAccumulator: pda(Buffer.from("yield_accumulator")),
+        gameStats: pda(Buffer.from("game_stats")),
+        pointsMint: pointsMintPda,
+        userPointsAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        payer,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const after = await program.account.user.fetch(userPda);
+    const tokensAfter = BigInt((await provider.connection.getTokenAccountBalance(userPointsAccount)).value.amount);
+    assert.equal(after.wonPoints.toNumber(), 0);
+    assert.equal(after.points.toString(), before.points.sub(before.wonPoints).toString());
+    assert.equal(tokensAfter.toString(), tokensBefore.toString());
+  });
+});
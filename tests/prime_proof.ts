@@ -0,0 +1,231 @@
+import * as anchor from '@coral-xyz/anchor';
+import { assert } from 'chai';
+import { PublicKey, SystemProgram, SYSVAR_SLOT_HASHES_PUBKEY } from '@solana/web3.js';
+import { createHash } from 'crypto';
+import { meetsDifficulty, winProofRound } from './helpers';
+
+describe('prime_slot_checker_prime_proof', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PrimeSlotChecker as anchor.Program<any>;
+
+  const pda = (seed: string) =>
+    PublicKey.findProgramAddressSync([Buffer.from(seed)], program.programId)[0];
+  const userPda = (seed: string) =>
+    PublicKey.findProgramAddressSync([Buffer.from(seed), provider.wallet.publicKey.toBuffer()], program.programId)[0];
+
+  const searchConfigPda = pda("search_config");
+  const searchDifficultyPda = pda("search_difficulty");
+  // Config initializers check the signer against the upgrade authority stored here
+  const programDataPda = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  )[0];
+
+  // Same mapping as math_utils::search_candidate
+  function searchCandidate(slotHash: Buffer, nonce: bigint): bigint {
+    const nonceBytes = Buffer.alloc(8);
+    nonceBytes.writeBigUInt64LE(nonce);
+    const hash = createHash('sha256')
+      .update(slotHash)
+      .update(provider.wallet.publicKey.toBuffer())
+      .update(nonceBytes)
+      .digest();
+    return hash.readBigUInt64LE(0);
+  }
+
+  function modPow(base: bigint, exp: bigint, mod: bigint): bigint {
+    let result = 1n;
+    base %= mod;
+    while (exp > 0n) {
+      if (exp & 1n) result = (result * base) % mod;
+      exp >>= 1n;
+      base = (base * base) % mod;
+    }
+    return result;
+  }
+
+  function isProbablePrime(n: bigint): boolean {
+    if (n < 4n) return n > 1n;
+    if (n % 2n === 0n) return false;
+    let d = n - 1n;
+    let r = 0;
+    while (d % 2n === 0n) { d /= 2n; r++; }
+    for (const a of [2n, 3n, 5n, 7n, 11n, 13n, 17n, 19n, 23n, 29n, 31n, 37n]) {
+      if (a % n === 0n) continue;
+      let x = modPow(a, d, n);
+      if (x === 1n || x === n - 1n) continue;
+      let composite = true;
+      for (let i = 1; i < r; i++) {
+        x = (x * x) % n;
+        if (x === n - 1n) { composite = false; break; }
+      }
+      if (composite) return false;
+    }
+    return true;
+  }
+
+  before(async () => {
+    // Primes ending in 3, an ending that can never be a SuperPrime's 01, one proof every 20 slots retargeted every 10
+    await program.methods
+      .initializeSearchConfig(1, new anchor.BN(3), new anchor.BN(20), new anchor.BN(10))
+      .accounts({
+        searchConfig: searchConfigPda,
+        searchDifficulty: searchDifficultyPda,
+        program: program.programId,
+        programData: programDataPda,
+        payer: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  it('Rejects proof suffixes that overlap the SuperPrime ending', async () => {
+    for (const [digits, suffix] of [[1, 1], [2, 1], [3, 301], [0, 0]]) {
+      try {
+        await program.methods
+          .setSearchDifficulty(digits, new anchor.BN(suffix))
+          .accounts({
+            searchConfig: searchConfigPda,
+            admin: provider.wallet.publicKey,
+          })
+          .rpc();
+        throw new Error(`Suffix ${suffix} (${digits} digits) should have been rejected`);
+      } catch (err: any) {
+        if (!err.toString().includes("InvalidSuffix")) throw err;
+      }
+    }
+  });
+
+  it('Searches off-chain for a prime and submits the nonce', async () => {
+    const config = await program.account.searchConfig.fetch(searchConfigPda);
+    const modulus = 10n ** BigInt(config.suffixDigits);
+    const suffix = BigInt(config.suffix.toString());
+
+    // Newest SlotHashes entry: u64 length, then (u64 slot, 32 byte hash) pairs
+    const sysvar = await provider.connection.getAccountInfo(SYSVAR_SLOT_HASHES_PUBKEY);
+    const slot = sysvar!.data.readBigUInt64LE(8);
+    const slotHash = sysvar!.data.subarray(16, 48);
+
+    const seasonState = await program.account.seasonState.fetch(pda("season_state"));
+    const seasonPda = PublicKey.findProgramAddressSync(
+      [Buffer.from("season"), seasonState.currentSeason.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+    const epochState = await program.account.epochState.fetch(pda("epoch_state"));
+    const epochPda = PublicKey.findProgramAddressSync(
+      [Buffer.from("epoch"), epochState.currentEpoch.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+    const statsBefore = await program.account.gameStats.fetch(pda("game_stats"));
+    const difficultyBefore = await program.account.difficulty.fetch(pda("difficulty"));
+    const searchDifficultyBefore = await program.account.difficulty.fetch(searchDifficultyPda);
+    const searchDifficulty = BigInt(searchDifficultyBefore.value.toString());
+
+    let nonce = 0n;
+    while (true) {
+      const candidate = searchCandidate(slotHash, nonce);
+      if (candidate % modulus === suffix && meetsDifficulty(candidate, searchDifficulty) && isProbablePrime(candidate)) break;
+      nonce++;
+    }
+    console.log(`Found prime for slot ${slot} at nonce ${nonce}`);
+
+    try {
+      const tx = await program.methods
+        .submitPrimeProof(new anchor.BN(slot.toString()), new anchor.BN(nonce.toString()))
+        .accounts({
+          user: userPda("user"),
+          searchState: userPda("search_state"),
+          searchConfig: searchConfigPda,
+          jackpot: pda("jackpot"),
+          totalWonPoints: pda("total_won_points"),
+          treasury: pda("treasury"),
+          playerList: pda("player_list"),
+          stakingTreasury: pda("staking_treasury"),
+          yieldAccumulator: pda("yield_accumulator"),
+          vestingConfig: pda("vesting_config"),
+          vesting: userPda("vesting"),
+          feeVault: pda("fee_vault"),
+          gameStats: pda("game_stats"),
+          winHistory: pda("win_history"),
+          seasonState: pda("season_state"),
+          season: seasonPda,
+          searchDifficulty: searchDifficultyPda,
+          epochState: pda("epoch_state"),
+          epoch: epochPda,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          payer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      console.log("Proof transaction signature:", tx);
+    } catch (err) {
+      console.error("Error in 'Searches off-chain for a prime and submits the nonce' test:", err);
+      throw new Error("Prime proof submission failed. Exiting tests.");
+    }
+
+    const state = await program.account.searchState.fetch(userPda("search_state"));
+    console.log('Proofs submitted:', state.proofs.toNumber());
+
+    // Counted like any other win, but never as a SuperPrime
+    const statsAfter = await program.account.gameStats.fetch(pda("game_stats"));
+    if (statsAfter.totalWins.toNumber() !== statsBefore.totalWins.toNumber() + 1) {
+      throw new Error("Proof win was not counted");
+    }
+    if (statsAfter.totalSuperprimes.toNumber() !== statsBefore.totalSuperprimes.toNumber()) {
+      throw new Error("Proof win was paid as a SuperPrime");
+    }
+    // Proofs retarget their own difficulty and leave the round difficulty to rounds
+    const searchDifficultyAfter = await program.account.difficulty.fetch(searchDifficultyPda);
+    const retargeted = searchDifficultyAfter.windowStartSlot.toString() !== searchDifficultyBefore.windowStartSlot.toString();
+    if (!retargeted && searchDifficultyAfter.winsSinceRetarget.toNumber() !== searchDifficultyBefore.winsSinceRetarget.toNumber() + 1) {
+      throw new Error("Proof win was not counted towards the proof difficulty retarget");
+    }
+    const difficultyAfter = await program.account.difficulty.fetch(pda("difficulty"));
+    assert.equal(difficultyAfter.winsSinceRetarget.toString(), difficultyBefore.winsSinceRetarget.toString());
+    assert.equal(difficultyAfter.value.toString(), difficultyBefore.value.toString());
+    const epoch = await program.account.epoch.fetch(epochPda);
+    const landedInEpoch = (await provider.connection.getSlot()) < epoch.endSlot.toNumber();
+    if (landedInEpoch && !epoch.winners.some((w: any) => w.winner.equals(provider.wallet.publicKey))) {
+      throw new Error("Proof win was not entered in the epoch draw");
+    }
+  });
+
+  it('Never lets repeated proofs take the treasury below its floor', async () => {
+    const treasury = pda("treasury");
+    const setPayout = (payoutBps: number, floor: anchor.BN) =>
+      program.methods
+        .setProofPayout(payoutBps, floor)
+        .accounts({ searchConfig: searchConfigPda, admin: provider.wallet.publicKey })
+        .rpc();
+    const config = await program.account.searchConfig.fetch(searchConfigPda);
+
+    // Leave only a sliver above the floor, then let the highest payout share chew at it
+    const info = await provider.connection.getAccountInfo(treasury);
+    const rent = await provider.connection.getMinimumBalanceForRentExemption(info.data.length);
+    const floor = new anchor.BN(Math.max(info.lamports - rent - 1_000_000, 0));
+    await setPayout(10_000, floor);
+    try {
+      for (let i = 0; i < 3; i++) {
+        await winProofRound(program);
+        assert.isAtLeast(await provider.connection.getBalance(treasury), rent + floor.toNumber());
+      }
+    } finally {
+      await setPayout(config.payoutBps, config.treasuryFloor);
+    }
+  });
+
+  it('Refuses payout shares above 100%', async () => {
+    try {
+      await program.methods
+        .setProofPayout(10_001, new anchor.BN(0))
+        .accounts({ searchConfig: searchConfigPda, admin: provider.wallet.publicKey })
+        .rpc();
+      assert.fail("A payout share above 10000 bps should be rejected");
+    } catch (err) {
+      assert.include(`${err}`, "InvalidProofPayout");
+    }
+  });
+});
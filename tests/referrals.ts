@@ -0,0 +1,102 @@
+import * as anchor from '@coral-xyz/anchor';
+import { assert } from 'chai';
+import { Keypair, LAMPORTS_PER_SOL, PublicKey, SystemProgram } from '@solana/web3.js';
+
+describe('prime_slot_checker_referrals', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PrimeSlotChecker as anchor.Program<any>;
+  const referrer = provider.wallet.publicKey;
+  const buyer = Keypair.generate();
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  const referralVaultPda = pda(Buffer.from("referral_vault"));
+  const referrerStatsPda = pda(Buffer.from("referrer"), referrer.toBuffer());
+  const buyerUserPda = pda(Buffer.from("user"), buyer.publicKey.toBuffer());
+  // Config initializers check the signer against the upgrade authority stored here
+  const programDataPda = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  )[0];
+
+  before(async () => {
+    if (!(await provider.connection.getAccountInfo(referralVaultPda))) {
+      await program.methods
+        .initializeReferralVault(500)
+        .accounts({ referralVault: referralVaultPda, program: program.programId, programData: programDataPda, payer: referrer, systemProgram: SystemProgram.programId })
+        .rpc();
+    }
+    if (!(await provider.connection.getAccountInfo(referrerStatsPda))) {
+      await program.methods
+        .registerReferrer()
+        .accounts({ referrerStats: referrerStatsPda, payer: referrer, systemProgram: SystemProgram.programId })
+        .rpc();
+    }
+
+    const airdrop = await provider.connection.requestAirdrop(buyer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdrop);
+    await program.methods
+      .initializeUser(0)
+      .accounts({ user: buyerUserPda, payer: buyer.publicKey, systemProgram: SystemProgram.programId })
+      .signers([buyer])
+      .rpc();
+  });
+
+  it('Credits the referrer on a referred purchase and binds them to the buyer', async () => {
+    const before = await program.account.referrerStats.fetch(referrerStatsPda);
+    const purchase = (referrerStats: PublicKey | null, referralVault: PublicKey | null) =>
+      program.methods
+        .payForPoints(new anchor.BN(1000), new anchor.BN(LAMPORTS_PER_SOL))
+        .accounts({
+          user: buyerUserPda,
+          treasury: pda(Buffer.from("treasury")),
+          stakingTreasury: pda(Buffer.from("staking_treasury")),
+          pricingConfig: pda(Buffer.from("pricing_config")),
+          totalWonPoints: pda(Buffer.from("total_won_points")),
+          yieldAccumulator: pda(Buffer.from("yield_accumulator")),
+          feeVault: pda(Buffer.from("fee_vault")),
+          gameStats: pda(Buffer.from("game_stats")),
+          seasonState: pda(Buffer.from("season_state")),
+          referrerStats,
+          referralVault,
+          payer: buyer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([buyer])
+        .rpc();
+
+    await purchase(referrerStatsPda, referralVaultPda);
+
+    const user = await program.account.user.fetch(buyerUserPda);
+    const stats = await program.account.referrerStats.fetch(referrerStatsPda);
+    assert.isTrue(user.referrer.equals(referrer));
+    assert.equal(stats.referredUsers.toNumber(), before.referredUsers.toNumber() + 1);
+    assert.isTrue(stats.earned.gt(before.earned));
+
+    try {
+      await purchase(null, null);
+      assert.fail("Purchases without the bound referrer should be rejected");
+    } catch (err) {
+      assert.include(`${err}`, "ReferrerMismatch");
+    }
+  });
+
+  it('Referrer claims their rewards', async () => {
+    const gameStatsPda = pda(Buffer.from("game_stats"));
+    const before = await program.account.referrerStats.fetch(referrerStatsPda);
+    const paidOut = (await program.account.gameStats.fetch(gameStatsPda)).totalPaidOut;
+
+    await program.methods
+      .claimReferralRewards()
+      .accounts({ referrerStats: referrerStatsPda, referralVault: referralVaultPda, gameStats: gameStatsPda, payer: referrer })
+      .rpc();
+
+    const stats = await program.account.referrerStats.fetch(referrerStatsPda);
+    assert.equal(stats.claimed.toString(), stats.earned.toString());
+    const claimed = stats.claimed.sub(before.claimed);
+    const gameStats = await program.account.gameStats.fetch(gameStatsPda);
+    assert.equal(gameStats.totalPaidOut.toString(), paidOut.add(claimed).toString());
+  });
+});
@@ -28,8 +28,17 @@ describe('prime_slot_checker', () => {
   let playerListBump: number;
   let leaderboardPda: PublicKey;
   let leaderboardBump: number;
-  let ratePda: PublicKey;
-  let rateBump: number;
+  let difficultyPda: PublicKey;
+  let epochStatePda: PublicKey;
+  let epochPda: PublicKey;
+  let seasonStatePda: PublicKey;
+  let seasonPda: PublicKey;
+  let yieldAccumulatorPda: PublicKey;
+  let vestingConfigPda: PublicKey;
+  let vestingPda: PublicKey;
+  let feeVaultPda: PublicKey;
+  let gameStatsPda: PublicKey;
+  let winHistoryPda: PublicKey;
 
   before(async () => {
     [jackpotPda, jackpotBump] = await PublicKey.findProgramAddress(
@@ -62,8 +71,60 @@ describe('prime_slot_checker', () => {
       program.programId
     );
 
-    [ratePda, rateBump] = await PublicKey.findProgramAddress(
-      [Buffer.from("rate")],
+    [difficultyPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("difficulty")],
+      program.programId
+    );
+
+    [yieldAccumulatorPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("yield_accumulator")],
+      program.programId
+    );
+
+    [vestingConfigPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("vesting_config")],
+      program.programId
+    );
+
+    [vestingPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("vesting"), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+
+    [feeVaultPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("fee_vault")],
+      program.programId
+    );
+
+    [gameStatsPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("game_stats")],
+      program.programId
+    );
+
+    [winHistoryPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("win_history")],
+      program.programId
+    );
+
+    [epochStatePda] = await PublicKey.findProgramAddress(
+      [Buffer.from("epoch_state")],
+      program.programId
+    );
+
+    const epochState = await program.account.epochState.fetch(epochStatePda);
+    [epochPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("epoch"), epochState.currentEpoch.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    [seasonStatePda] = await PublicKey.findProgramAddress(
+      [Buffer.from("season_state")],
+      program.programId
+    );
+
+    const seasonState = await program.account.seasonState.fetch(seasonStatePda);
+    [seasonPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("season"), seasonState.currentSeason.toArrayLike(Buffer, "le", 8)],
       program.programId
     );
 
@@ -73,7 +134,6 @@ describe('prime_slot_checker', () => {
     await program.account.totalWonPoints.fetch(totalWonPointsPda);
     await program.account.playerList.fetch(playerListPda);
     await program.account.leaderboard.fetch(leaderboardPda);
-    await program.account.rate.fetch(ratePda);
   });
 
   it('Play against current slot until jackpot is 0', async () => {
@@ -110,8 +170,19 @@ describe('prime_slot_checker', () => {
             totalWonPoints: totalWonPointsPda,
             playerList: playerListPda,
             leaderboard: leaderboardPda,
-            rate: ratePda,
+            difficulty: difficultyPda,
+            epochState: epochStatePda,
+            epoch: epochPda,
+            yieldAccumulator: yieldAccumulatorPda,
+            vestingConfig: vestingConfigPda,
+            vesting: vestingPda,
+            feeVault: feeVaultPda,
+            gameStats: gameStatsPda,
+            winHistory: winHistoryPda,
+            seasonState: seasonStatePda,
+            season: seasonPda,
             payer: provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
           }).preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 300000 }),]).rpc();
 
         console.log("\nTransaction signature:", tx);
@@ -123,7 +194,6 @@ describe('prime_slot_checker', () => {
         jackpotAccount = await program.account.jackpot.fetch(jackpotPda);
         userAccount = await program.account.user.fetch(userPda);
         const totalWonPointsAccount = await program.account.totalWonPoints.fetch(totalWonPointsPda);
-        const rateAccount = await program.account.rate.fetch(ratePda);
         const payerBalance = await provider.connection.getBalance(provider.wallet.publicKey);
         const treasuryBalance = await provider.connection.getBalance(treasuryPda);
         const stakingTreasuryBalance = await provider.connection.getBalance(stakingTreasuryPda);
@@ -132,7 +202,6 @@ describe('prime_slot_checker', () => {
         console.log('Updated User Points:', userAccount.points.toNumber());
         console.log('Updated User Won Points:', userAccount.wonPoints ? userAccount.wonPoints.toNumber() : 0);
         console.log('Updated Total Won Points:', totalWonPointsAccount.points.toNumber());
-        console.log('Updated Rate:', rateAccount.value);
         console.log('Jackpot Winner Pubkey:', jackpotAccount.winner.toBase58());
         console.log('Payer Balance:', payerBalance / 1000000000 + " SOL");
         console.log('Treasury Balance:', treasuryBalance / 1000000000 + " SOL");
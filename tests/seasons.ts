@@ -0,0 +1,124 @@
+import * as anchor from '@coral-xyz/anchor';
+import { assert } from 'chai';
+import { PublicKey, SystemProgram } from '@solana/web3.js';
+import { winProofRound } from './helpers';
+
+describe('prime_slot_checker_seasons', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PrimeSlotChecker as anchor.Program<any>;
+  const admin = provider.wallet.publicKey;
+
+  const seasonStatePda = PublicKey.findProgramAddressSync([Buffer.from("season_state")], program.programId)[0];
+  const gameStatsPda = PublicKey.findProgramAddressSync([Buffer.from("game_stats")], program.programId)[0];
+  const seasonPda = (id: anchor.BN) =>
+    PublicKey.findProgramAddressSync([Buffer.from("season"), id.toArrayLike(Buffer, "le", 8)], program.programId)[0];
+  const userPda = PublicKey.findProgramAddressSync([Buffer.from("user"), admin.toBuffer()], program.programId)[0];
+
+  // A proof win puts the wallet on this season's (or, past end_slot, the next season's) board
+  before(async () => {
+    await winProofRound(program);
+  });
+
+  it('Keeps the season standings ranked by won points', async () => {
+    const seasonState = await program.account.seasonState.fetch(seasonStatePda);
+    const season = await program.account.season.fetch(seasonPda(seasonState.currentSeason));
+
+    assert.isAtMost(season.standings.length, 10);
+    for (let i = 1; i < season.standings.length; i++) {
+      assert.isTrue(season.standings[i - 1].wonPoints.gte(season.standings[i].wonPoints));
+    }
+    assert.isAtMost(seasonState.distribution.reduce((sum: number, bps: number) => sum + bps, 0), 10_000);
+    console.log(`Season ${season.seasonId} until slot ${season.endSlot}, pool ${seasonState.pool}:`,
+      season.standings.map((entry: any) => ({ player: entry.player.toBase58(), wonPoints: entry.wonPoints.toString() })));
+  });
+
+  it('Ranks players by the season points kept on their profile', async () => {
+    const seasonState = await program.account.seasonState.fetch(seasonStatePda);
+    const season = await program.account.season.fetch(seasonPda(seasonState.currentSeason));
+    const user = await program.account.user.fetch(userPda);
+    assert.isAbove(user.seasonPoints.toNumber(), 0);
+
+    // Wins after end_slot are buffered for the next season rather than dropped
+    const late = user.seasonId.gt(season.seasonId);
+    const board = late ? season.lateStandings : season.standings;
+    assert.equal(user.seasonId.toString(), season.seasonId.addn(late ? 1 : 0).toString());
+    const entry = board.find((e: any) => e.player.equals(admin));
+    if (entry) {
+      assert.equal(entry.wonPoints.toString(), user.seasonPoints.toString());
+    } else {
+      assert.equal(board.length, 10);
+      assert.isTrue(board[board.length - 1].wonPoints.gte(user.seasonPoints));
+    }
+  });
+
+  it('Rejects distributions over 100% or with too many ranks', async () => {
+    const seasonState = await program.account.seasonState.fetch(seasonStatePda);
+    // Only the upgrade authority can open seasons, so the test wallet is their admin
+    assert.isTrue(seasonState.admin.equals(admin));
+
+    for (const distribution of [[6000, 5000], Array(11).fill(100), []]) {
+      try {
+        await program.methods.setSeasonConfig(seasonState.poolBps, distribution).accounts({ seasonState: seasonStatePda, admin }).rpc();
+        assert.fail(`Distribution ${distribution} should be rejected`);
+      } catch (err) {
+        assert.include(`${err}`, "InvalidSeasonParams");
+      }
+    }
+  });
+
+  it('Finalizes the current season once its end slot has passed', async () => {
+    const seasonState = await program.account.seasonState.fetch(seasonStatePda);
+    const currentId = seasonState.currentSeason;
+    const season = await program.account.season.fetch(seasonPda(currentId));
+    const slot = await provider.connection.getSlot();
+
+    const finalize = () =>
+      program.methods
+        .finalizeSeason()
+        .accounts({
+          seasonState: seasonStatePda,
+          season: seasonPda(currentId),
+          nextSeason: seasonPda(currentId.addn(1)),
+          gameStats: gameStatsPda,
+          payer: admin,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(season.standings.map((entry: any) => ({ pubkey: entry.player, isSigner: false, isWritable: true })))
+        .rpc();
+
+    if (slot < season.endSlot.toNumber()) {
+      try {
+        await finalize();
+        assert.fail('An open season should not finalize');
+      } catch (err) {
+        assert.include(`${err}`, "SeasonStillOpen");
+      }
+      console.log(`Season ${currentId} is open until slot ${season.endSlot}, nothing to crank.`);
+      return;
+    }
+
+    await finalize();
+    const archived = await program.account.season.fetch(seasonPda(currentId));
+    assert.isTrue(archived.finalized);
+    assert.equal(archived.paid.add(archived.rolledForward).toString(), archived.pot.toString());
+    archived.standings.forEach((entry: any, rank: number) => {
+      const share = seasonState.distribution[rank] ?? 0;
+      assert.equal(entry.payout.toString(), archived.pot.muln(share).divn(10_000).toString());
+    });
+
+    const next = await program.account.season.fetch(seasonPda(currentId.addn(1)));
+    assert.equal(next.carriedIn.toString(), archived.rolledForward.toString());
+    assert.deepEqual(
+      next.standings.map((entry: any) => entry.player.toBase58()),
+      archived.lateStandings.map((entry: any) => entry.player.toBase58()),
+    );
+    console.log('Archived season:', {
+      seasonId: archived.seasonId.toString(),
+      pot: archived.pot.toString(),
+      rolledForward: archived.rolledForward.toString(),
+      standings: archived.standings.map((entry: any) => ({ player: entry.player.toBase58(), payout: entry.payout.toString() })),
+    });
+  });
+});
@@ -0,0 +1,85 @@
+import * as anchor from '@coral-xyz/anchor';
+import { assert } from 'chai';
+import { PublicKey, SystemProgram } from '@solana/web3.js';
+import { winProofRound } from './helpers';
+
+describe('prime_slot_checker_stake_points', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PrimeSlotChecker as anchor.Program<any>;
+  const payer = provider.wallet.publicKey;
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  const positionId = new anchor.BN(Date.now());
+  const userPda = pda(Buffer.from("user"), payer.toBuffer());
+  const stakePositionPda = pda(Buffer.from("stake"), payer.toBuffer(), positionId.toArrayLike(Buffer, "le", 8));
+  const yieldAccumulatorPda = pda(Buffer.from("yield_accumulator"));
+  const totalWonPointsPda = pda(Buffer.from("total_won_points"));
+
+  before(async () => {
+    // A proof win guarantees won points to stake
+    await winProofRound(program);
+  });
+
+  it('Rejects a lock shorter than the first tier', async () => {
+    try {
+      await program.methods
+        .stakePoints(positionId, new anchor.BN(1), new anchor.BN(10))
+        .accounts({
+          user: userPda,
+          stakePosition: stakePositionPda,
+          yieldAccumulator: yieldAccumulatorPda,
+          totalWonPoints: totalWonPointsPda,
+          payer,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("Short lock should have been rejected");
+    } catch (err) {
+      assert.include(`${err}`, "LockTooShort");
+    }
+  });
+
+  it('Stakes won points and refuses to unstake while locked', async () => {
+    const user = await program.account.user.fetch(userPda);
+    assert.isAbove(user.wonPoints.toNumber(), 0);
+
+    try {
+      await program.methods
+        .stakePoints(positionId, user.wonPoints, new anchor.BN(1_512_000))
+        .accounts({
+          user: userPda,
+          stakePosition: stakePositionPda,
+          yieldAccumulator: yieldAccumulatorPda,
+          totalWonPoints: totalWonPointsPda,
+          payer,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    } catch (err) {
+      console.error("Error in stakePoints:", err);
+      throw new Error("Test 'Stakes won points' failed. Exiting tests.");
+    }
+
+    const position = await program.account.stakePosition.fetch(stakePositionPda);
+    console.log('Stake position:', position.amount.toString(), 'weight:', position.weight.toString());
+    assert.equal(position.weight.toString(), position.amount.muln(12_500).divn(10_000).toString());
+
+    try {
+      await program.methods
+        .unstakePoints(positionId)
+        .accounts({
+          user: userPda,
+          stakePosition: stakePositionPda,
+          yieldAccumulator: yieldAccumulatorPda,
+          payer,
+        })
+        .rpc();
+      assert.fail("Locked stake should not unstake");
+    } catch (err) {
+      assert.include(`${err}`, "StakeLocked");
+    }
+  });
+});
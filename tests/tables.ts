@@ -0,0 +1,186 @@
+import * as anchor from '@coral-xyz/anchor';
+import { PublicKey, SystemProgram, LAMPORTS_PER_SOL } from '@solana/web3.js';
+import { ComputeBudgetProgram } from "@solana/web3.js";
+import { assert } from 'chai';
+
+describe('prime_slot_checker_tables', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PrimeSlotChecker as anchor.Program<any>;
+
+  // Fresh id per run so the test never collides with a live table
+  const tableId = new anchor.BN(Date.now());
+  const tableSeed = tableId.toArrayLike(Buffer, "le", 8);
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  const tablePda = pda(Buffer.from("table"), tableSeed);
+  const jackpotPda = pda(Buffer.from("jackpot"), tableSeed);
+  const treasuryPda = pda(Buffer.from("treasury"), tableSeed);
+  const playerListPda = pda(Buffer.from("player_list"), tableSeed);
+  const seatPda = pda(Buffer.from("seat"), tableSeed, provider.wallet.publicKey.toBuffer());
+
+  it('Creates a private low-stakes table', async () => {
+    const config = {
+      entryCost: new anchor.BN(2),
+      basePowerUpBps: 500,
+      powerUpTiers: [
+        { minSlots: new anchor.BN(50), bps: 1000 },
+        { minSlots: new anchor.BN(150), bps: 2000 },
+        { minSlots: new anchor.BN(300), bps: 3000 },
+      ],
+      superprimeDigits: 0,
+      superprimeSuffix: new anchor.BN(0),
+      private: true,
+    };
+
+    await program.methods
+      .createTable(tableId, config)
+      .accounts({
+        table: tablePda,
+        jackpot: jackpotPda,
+        treasury: treasuryPda,
+        playerList: playerListPda,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .fundTable(tableId, new anchor.BN(LAMPORTS_PER_SOL / 10))
+      .accounts({
+        table: tablePda,
+        treasury: treasuryPda,
+        payer: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const table = await program.account.table.fetch(tablePda);
+    console.log('Table:', table.tableId.toString(), 'entry cost:', table.config.entryCost.toString());
+  });
+
+  it('Rejects unseated players and admits seated ones', async () => {
+    const playAccounts = (seat: PublicKey | null) => ({
+      user: pda(Buffer.from("user"), provider.wallet.publicKey.toBuffer()),
+      table: tablePda,
+      seat,
+      jackpot: jackpotPda,
+      treasury: treasuryPda,
+      playerList: playerListPda,
+      totalWonPoints: pda(Buffer.from("total_won_points")),
+      stakingTreasury: pda(Buffer.from("staking_treasury")),
+      yieldAccumulator: pda(Buffer.from("yield_accumulator")),
+      vestingConfig: pda(Buffer.from("vesting_config")),
+      vesting: pda(Buffer.from("vesting"), provider.wallet.publicKey.toBuffer()),
+      feeVault: pda(Buffer.from("fee_vault")),
+      gameStats: pda(Buffer.from("game_stats")),
+      difficulty: pda(Buffer.from("difficulty")),
+      payer: provider.wallet.publicKey,
+      systemProgram: SystemProgram.programId,
+    });
+
+    let rejected = false;
+    try {
+      await program.methods.checkTableSlot(tableId).accounts(playAccounts(null)).rpc();
+    } catch (err) {
+      rejected = true;
+    }
+    if (!rejected) {
+      throw new Error("Unseated player should not be able to play a private table.");
+    }
+
+    await program.methods
+      .addTablePlayer(tableId, provider.wallet.publicKey)
+      .accounts({
+        table: tablePda,
+        seat: seatPda,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const totalBefore = await program.account.totalWonPoints.fetch(pda(Buffer.from("total_won_points")));
+    const difficultyBefore = await program.account.difficulty.fetch(pda(Buffer.from("difficulty")));
+    const userBefore = await program.account.user.fetch(pda(Buffer.from("user"), provider.wallet.publicKey.toBuffer()));
+    const tx = await program.methods
+      .checkTableSlot(tableId)
+      .accounts(playAccounts(seatPda))
+      .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 300000 })])
+      .rpc();
+    console.log("Table round transaction signature:", tx);
+
+    const jackpot = await program.account.jackpot.fetch(jackpotPda);
+    console.log('Table jackpot:', jackpot.amount.toNumber());
+
+    // Win or lose, a table round never adds to the won points sharing the global yield
+    const totalAfter = await program.account.totalWonPoints.fetch(pda(Buffer.from("total_won_points")));
+    if (totalAfter.points.gt(totalBefore.points)) {
+      throw new Error("Table round should not add to total won points.");
+    }
+
+    // Only a win that passed the difficulty filter counts towards the retarget
+    const difficultyAfter = await program.account.difficulty.fetch(pda(Buffer.from("difficulty")));
+    const userAfter = await program.account.user.fetch(pda(Buffer.from("user"), provider.wallet.publicKey.toBuffer()));
+    const won = userAfter.wins.gt(userBefore.wins);
+    const retargeted = !difficultyAfter.windowStartSlot.eq(difficultyBefore.windowStartSlot);
+    if (!retargeted) {
+      assert.equal(difficultyAfter.winsSinceRetarget.toNumber(), difficultyBefore.winsSinceRetarget.toNumber() + (won ? 1 : 0));
+    }
+  });
+
+  it('Refuses to close a table whose treasury still holds deposits', async () => {
+    await program.methods
+      .removeTablePlayer(tableId, provider.wallet.publicKey)
+      .accounts({ table: tablePda, seat: seatPda, authority: provider.wallet.publicKey })
+      .rpc();
+
+    try {
+      await program.methods
+        .closeTable(tableId)
+        .accounts({
+          table: tablePda,
+          jackpot: jackpotPda,
+          treasury: treasuryPda,
+          playerList: playerListPda,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+      throw new Error("Funded table should not close.");
+    } catch (err: any) {
+      if (!err.toString().includes("TableTreasuryNotEmpty")) throw err;
+    }
+  });
+
+  it('Closes an unfunded table and returns the rent to the authority', async () => {
+    const emptyId = tableId.addn(1);
+    const emptySeed = emptyId.toArrayLike(Buffer, "le", 8);
+    const accounts = {
+      table: pda(Buffer.from("table"), emptySeed),
+      jackpot: pda(Buffer.from("jackpot"), emptySeed),
+      treasury: pda(Buffer.from("treasury"), emptySeed),
+      playerList: pda(Buffer.from("player_list"), emptySeed),
+      authority: provider.wallet.publicKey,
+    };
+
+    await program.methods
+      .createTable(emptyId, {
+        entryCost: new anchor.BN(2),
+        basePowerUpBps: 500,
+        powerUpTiers: [],
+        superprimeDigits: 0,
+        superprimeSuffix: new anchor.BN(0),
+        private: false,
+      })
+      .accounts({ ...accounts, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    await program.methods.closeTable(emptyId).accounts(accounts).rpc();
+
+    const info = await provider.connection.getAccountInfo(accounts.table);
+    if (info !== null) {
+      throw new Error("Table account should be closed.");
+    }
+  });
+});
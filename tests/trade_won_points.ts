@@ -1,5 +1,7 @@
 import * as anchor from '@coral-xyz/anchor';
+import { assert } from 'chai';
 import { PublicKey, SystemProgram } from '@solana/web3.js';
+import { winProofRound } from './helpers';
 
 describe('prime_slot_checker', () => {
   const provider = anchor.AnchorProvider.env();
@@ -21,8 +23,8 @@ describe('prime_slot_checker', () => {
   let playerListBump: number;
   let leaderboardPda: PublicKey;
   let leaderboardBump: number;
-  let ratePda: PublicKey;
-  let rateBump: number;
+  let yieldAccumulatorPda: PublicKey;
+  let gameStatsPda: PublicKey;
 
   before(async () => {
     [jackpotPda, jackpotBump] = await PublicKey.findProgramAddress(
@@ -55,8 +57,13 @@ describe('prime_slot_checker', () => {
       program.programId
     );
 
-    [ratePda, rateBump] = await PublicKey.findProgramAddress(
-      [Buffer.from("rate")],
+    [yieldAccumulatorPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("yield_accumulator")],
+      program.programId
+    );
+
+    [gameStatsPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("game_stats")],
       program.programId
     );
 
@@ -66,62 +73,112 @@ describe('prime_slot_checker', () => {
     await program.account.totalWonPoints.fetch(totalWonPointsPda);
     await program.account.playerList.fetch(playerListPda);
     await program.account.leaderboard.fetch(leaderboardPda);
-    await program.account.rate.fetch(ratePda);
   });
 
   it('Trade won points for lamports', async () => {
-    let userAccount;
-    let totalWonPoints;
-
-    try {
-      [userPda, userBump] = await PublicKey.findProgramAddress(
-        [Buffer.from("user"), provider.wallet.publicKey.toBuffer()],
-        program.programId
-      );
-
-      userAccount = await program.account.user.fetch(userPda);
-    } catch (err) {
-      console.error("Fetch Error before 'Trade won points for lamports':", err);
-      console.error("Problematic Accounts:");
-      console.error(`User Account: ${userPda ? userPda.toBase58() : 'undefined'}`);
-      throw new Error("Fetch failed. Exiting tests.");
+    [userPda, userBump] = await PublicKey.findProgramAddress(
+      [Buffer.from("user"), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+
+    // A trade is a fixed lot of 1000 won points, win until there is one to trade
+    for (let attempt = 0; attempt < 5; attempt++) {
+      if ((await program.account.user.fetch(userPda)).wonPoints.gten(1000)) break;
+      await winProofRound(program);
     }
+    const userAccount = await program.account.user.fetch(userPda);
+    assert.isTrue(userAccount.wonPoints.gten(1000), "Not enough won points to trade");
+
+    const quote = await program.methods
+      .quoteRedemption(new anchor.BN(1000))
+      .accounts({ user: userPda, owner: provider.wallet.publicKey, yieldAccumulator: yieldAccumulatorPda })
+      .view();
+    const stakingBefore = await provider.connection.getBalance(stakingTreasuryPda);
+
+    const tx = await program.methods
+      .tradeWonPoints(userBump, quote)
+      .accounts({
+        user: userPda,
+        stakingTreasury: stakingTreasuryPda,
+        totalWonPoints: totalWonPointsPda,
+        yieldAccumulator: yieldAccumulatorPda,
+        gameStats: gameStatsPda,
+        pointsMint: null,
+        userPointsAccount: null,
+        tokenProgram: null,
+        payer: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    console.log("\nTransaction signature:", tx);
+
+    // The lot is paid exactly its quoted share of the accrued yield, out of the yield pool
+    const updated = await program.account.user.fetch(userPda);
+    const stakingAfter = await provider.connection.getBalance(stakingTreasuryPda);
+    assert.equal(updated.lastClaimedLamports.toString(), quote.toString());
+    assert.equal(stakingBefore - stakingAfter, quote.toNumber());
+    assert.equal(updated.wonPoints.toString(), userAccount.wonPoints.subn(1000).toString());
+    console.log(`Traded 1000 won points for ${quote.toString()} lamports`);
+  });
 
-    try {
-      const tx = await program.methods
-        .tradeWonPoints(userBump)
+  describe('after a proof win', () => {
+    // The trade above may have used up the won points, so win a fresh batch to redeem
+    before(async () => {
+      await winProofRound(program);
+    });
+
+    it('Redeems part of the won points at the quoted price', async () => {
+      const userAccount = await program.account.user.fetch(userPda);
+      const amount = userAccount.wonPoints.divn(2);
+      assert.isFalse(amount.isZero());
+
+      const quote = await program.methods
+        .quoteRedemption(amount)
+        .accounts({ user: userPda, owner: provider.wallet.publicKey, yieldAccumulator: yieldAccumulatorPda })
+        .view();
+      console.log(`Quoted ${quote.toString()} lamports for ${amount.toString()} won points`);
+
+      try {
+        await program.methods
+          .redeemWonPoints(amount, quote.addn(1))
+          .accounts({
+            user: userPda,
+            totalWonPoints: totalWonPointsPda,
+            stakingTreasury: stakingTreasuryPda,
+            yieldAccumulator: yieldAccumulatorPda,
+            gameStats: gameStatsPda,
+            pointsMint: null,
+            userPointsAccount: null,
+            tokenProgram: null,
+            payer: provider.wallet.publicKey,
+          })
+          .rpc();
+        assert.fail("Redemption above the quote should have been rejected");
+      } catch (err) {
+        assert.include(`${err}`, "SlippageExceeded");
+      }
+
+      const stakingBefore = await provider.connection.getBalance(stakingTreasuryPda);
+      await program.methods
+        .redeemWonPoints(amount, quote)
         .accounts({
           user: userPda,
-          rate: ratePda,
+          totalWonPoints: totalWonPointsPda,
           stakingTreasury: stakingTreasuryPda,
+          yieldAccumulator: yieldAccumulatorPda,
+          gameStats: gameStatsPda,
+          pointsMint: null,
+          userPointsAccount: null,
+          tokenProgram: null,
           payer: provider.wallet.publicKey,
-          systemProgram: SystemProgram.programId,
         })
         .rpc();
 
-      console.log("\nTransaction signature:", tx);
-    } catch (err) {
-      console.error("Transaction failed:", err);
-    }
-
-    try {
-      userAccount = await program.account.user.fetch(userPda);
-      totalWonPoints = await program.account.totalWonPoints.fetch(totalWonPointsPda);
-      const rateAccount = await program.account.rate.fetch(ratePda);
-      const payerBalance = await provider.connection.getBalance(provider.wallet.publicKey);
-      const stakingTreasuryBalance = await provider.connection.getBalance(stakingTreasuryPda);
-
-      console.log('Updated User Won Points:', userAccount.wonPoints ? userAccount.wonPoints.toNumber() : 0);
-      console.log('Updated Rate:', rateAccount.value);
-      console.log('Payer Balance:', payerBalance / 1000000000 + " SOL");
-      console.log('Staking Treasury Balance:', stakingTreasuryBalance / 1000000000 + " SOL");
-      console.log('Total Won Points:', totalWonPoints.points.toNumber());
-    } catch (err) {
-      console.error("Fetch Error after trade:", err);
-      console.error("Problematic Accounts:");
-      console.error(`User Account: ${userPda.toBase58()}`);
-      throw new Error("Fetch failed. Exiting tests.");
-    }
+      const updated = await program.account.user.fetch(userPda);
+      assert.equal(updated.wonPoints.toString(), userAccount.wonPoints.sub(amount).toString());
+      assert.equal(updated.points.toString(), userAccount.points.sub(amount).toString());
+      assert.equal(stakingBefore - await provider.connection.getBalance(stakingTreasuryPda), quote.toNumber());
+    });
   });
 });
 
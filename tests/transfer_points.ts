@@ -0,0 +1,85 @@
+import * as anchor from '@coral-xyz/anchor';
+import { assert } from 'chai';
+import { Keypair, LAMPORTS_PER_SOL, PublicKey, SystemProgram } from '@solana/web3.js';
+import { buyPoints } from './helpers';
+
+describe('prime_slot_checker_transfer_points', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PrimeSlotChecker as anchor.Program<any>;
+  const payer = provider.wallet.publicKey;
+  const teammate = Keypair.generate();
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  const transferConfigPda = pda(Buffer.from("transfer_config"));
+  // Config initializers check the signer against the upgrade authority stored here
+  const programDataPda = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  )[0];
+  const userPda = pda(Buffer.from("user"), payer.toBuffer());
+  const teammateUserPda = pda(Buffer.from("user"), teammate.publicKey.toBuffer());
+
+  const transfer = (amount: number, recipient: PublicKey, recipientUser: PublicKey) =>
+    program.methods
+      .transferPoints(new anchor.BN(amount), recipient)
+      .accounts({
+        user: userPda,
+        recipientUser,
+        transferConfig: transferConfigPda,
+        yieldAccumulator: pda(Buffer.from("yield_accumulator")),
+        payer,
+      })
+      .rpc();
+
+  before(async () => {
+    if (!(await provider.connection.getAccountInfo(transferConfigPda))) {
+      await program.methods
+        .initializeTransferConfig(false, new anchor.BN(10_000))
+        .accounts({ transferConfig: transferConfigPda, program: program.programId, programData: programDataPda, payer, systemProgram: SystemProgram.programId })
+        .rpc();
+    }
+
+    const airdrop = await provider.connection.requestAirdrop(teammate.publicKey, LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdrop);
+    await program.methods
+      .initializeUser(0)
+      .accounts({ user: teammateUserPda, payer: teammate.publicKey, systemProgram: SystemProgram.programId })
+      .signers([teammate])
+      .rpc();
+
+    // Fresh bought points, only those move while won points stay non-transferable
+    await buyPoints(program);
+  });
+
+  it('Sends points to a teammate', async () => {
+    const sender = await program.account.user.fetch(userPda);
+    assert.isTrue(sender.points.sub(sender.wonPoints).gten(10));
+
+    await transfer(10, teammate.publicKey, teammateUserPda);
+
+    const updatedSender = await program.account.user.fetch(userPda);
+    const receiver = await program.account.user.fetch(teammateUserPda);
+    assert.equal(updatedSender.points.toString(), sender.points.subn(10).toString());
+    assert.equal(receiver.points.toNumber(), 10);
+  });
+
+  it('Rejects transfers to yourself and above the daily cap', async () => {
+    try {
+      await transfer(1, payer, userPda);
+      assert.fail("Self transfer should have been rejected");
+    } catch (err) {
+      assert.include(`${err}`, "SelfTransfer");
+    }
+
+    const config = await program.account.transferConfig.fetch(transferConfigPda);
+    try {
+      await transfer(config.dailyCap.toNumber() + 1, teammate.publicKey, teammateUserPda);
+      assert.fail("Transfer above the daily cap should have been rejected");
+    } catch (err) {
+      assert.match(`${err}`, /DailyTransferCapExceeded|InsufficientFunds|insufficient funds/);
+    }
+  });
+});
@@ -0,0 +1,108 @@
+import * as anchor from '@coral-xyz/anchor';
+import { assert } from 'chai';
+import { PublicKey, ComputeBudgetProgram, SystemProgram } from '@solana/web3.js';
+import { winProofRound } from './helpers';
+
+describe('prime_slot_checker_user_stats', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PrimeSlotChecker as anchor.Program<any>;
+  const payer = provider.wallet.publicKey;
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+  const userPda = pda(Buffer.from("user"), payer.toBuffer());
+
+  it('Counts a played round in the user profile', async () => {
+    const epochStatePda = pda(Buffer.from("epoch_state"));
+    const epochState = await program.account.epochState.fetch(epochStatePda);
+    const seasonStatePda = pda(Buffer.from("season_state"));
+    const seasonState = await program.account.seasonState.fetch(seasonStatePda);
+    const before = await program.account.user.fetch(userPda);
+
+    await program.methods
+      .checkSlot(0)
+      .accounts({
+        user: userPda,
+        jackpot: pda(Buffer.from("jackpot")),
+        treasury: pda(Buffer.from("treasury")),
+        stakingTreasury: pda(Buffer.from("staking_treasury")),
+        totalWonPoints: pda(Buffer.from("total_won_points")),
+        playerList: pda(Buffer.from("player_list")),
+        difficulty: pda(Buffer.from("difficulty")),
+        epochState: epochStatePda,
+        epoch: pda(Buffer.from("epoch"), epochState.currentEpoch.toArrayLike(Buffer, "le", 8)),
+        yieldAccumulator: pda(Buffer.from("yield_accumulator")),
+        vestingConfig: pda(Buffer.from("vesting_config")),
+        vesting: pda(Buffer.from("vesting"), payer.toBuffer()),
+        feeVault: pda(Buffer.from("fee_vault")),
+        gameStats: pda(Buffer.from("game_stats")),
+        winHistory: pda(Buffer.from("win_history")),
+        seasonState: seasonStatePda,
+        season: pda(Buffer.from("season"), seasonState.currentSeason.toArrayLike(Buffer, "le", 8)),
+        payer,
+        systemProgram: SystemProgram.programId,
+      })
+      .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 300000 })])
+      .rpc();
+
+    const after = await program.account.user.fetch(userPda);
+    assert.equal(after.roundsPlayed.toNumber(), before.roundsPlayed.toNumber() + 1);
+    assert.isTrue(after.lastPlaySlot.gt(before.lastPlaySlot));
+    assert.isFalse(after.firstPlaySlot.isZero());
+    assert.isTrue(after.firstPlaySlot.lte(after.lastPlaySlot));
+
+    const won = after.wins.toNumber() > before.wins.toNumber();
+    if (won) {
+      assert.equal(after.currentStreak.toNumber(), before.currentStreak.toNumber() + 1);
+      assert.isTrue(after.lamportsWon.gte(before.lamportsWon));
+      assert.isAbove(after.bestPowerUpBps, 0);
+    } else {
+      assert.equal(after.currentStreak.toNumber(), 0);
+      assert.equal(after.lamportsWon.toString(), before.lamportsWon.toString());
+    }
+    assert.isTrue(after.bestStreak.gte(after.currentStreak));
+    console.log('Profile:', {
+      rounds: after.roundsPlayed.toString(),
+      wins: after.wins.toString(),
+      superprimes: after.superprimes.toString(),
+      bestPowerUpBps: after.bestPowerUpBps,
+      lamportsWon: after.lamportsWon.toString(),
+      lamportsClaimed: after.lamportsClaimed.toString(),
+      streak: `${after.currentStreak.toString()} (best ${after.bestStreak.toString()})`,
+    });
+  });
+
+  describe('after a proof win', () => {
+    // A proof win guarantees won points to redeem
+    before(async () => {
+      await winProofRound(program);
+    });
+
+    it('Counts redeemed yield as claimed lamports', async () => {
+      const before = await program.account.user.fetch(userPda);
+      assert.isAbove(before.wonPoints.toNumber(), 0);
+
+      await program.methods
+        .redeemWonPoints(new anchor.BN(1), new anchor.BN(0))
+        .accounts({
+          user: userPda,
+          totalWonPoints: pda(Buffer.from("total_won_points")),
+          stakingTreasury: pda(Buffer.from("staking_treasury")),
+          yieldAccumulator: pda(Buffer.from("yield_accumulator")),
+          gameStats: pda(Buffer.from("game_stats")),
+          pointsMint: null,
+          userPointsAccount: null,
+          tokenProgram: null,
+          payer,
+        })
+        .rpc();
+
+      const after = await program.account.user.fetch(userPda);
+      assert.equal(
+        after.lamportsClaimed.toString(),
+        before.lamportsClaimed.add(after.lastClaimedLamports).toString(),
+      );
+    });
+  });
+});
@@ -0,0 +1,91 @@
+import * as anchor from '@coral-xyz/anchor';
+import { assert } from 'chai';
+import { Keypair, PublicKey } from '@solana/web3.js';
+import { fundPlayer, winProofRound } from './helpers';
+
+describe('prime_slot_checker_vesting', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PrimeSlotChecker as anchor.Program<any>;
+  const payer = provider.wallet.publicKey;
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  const vestingConfigPda = pda(Buffer.from("vesting_config"));
+  const vestingPda = pda(Buffer.from("vesting"), payer.toBuffer());
+  const winner = Keypair.generate();
+  const winnerVestingPda = pda(Buffer.from("vesting"), winner.publicKey.toBuffer());
+
+  it('Only the admin can change the vesting terms', async () => {
+    const config = await program.account.vestingConfig.fetch(vestingConfigPda);
+    const stranger = Keypair.generate();
+
+    try {
+      await program.methods
+        .setVestingConfig(new anchor.BN(0), 0, new anchor.BN(1))
+        .accounts({ vestingConfig: vestingConfigPda, admin: stranger.publicKey })
+        .signers([stranger])
+        .rpc();
+      assert.fail("Non-admin should not change vesting terms");
+    } catch (err) {
+      assert.include(`${err}`, "Unauthorized");
+    }
+
+    if (!config.admin.equals(payer)) {
+      return;
+    }
+    try {
+      await program.methods
+        .setVestingConfig(config.thresholdLamports, 10_001, config.vestingSlots)
+        .accounts({ vestingConfig: vestingConfigPda, admin: payer })
+        .rpc();
+      assert.fail("Immediate share above 100% should be rejected");
+    } catch (err) {
+      assert.include(`${err}`, "InvalidVestingConfig");
+    }
+  });
+
+  it('Claims whatever has vested so far', async () => {
+    const vesting = await program.account.vesting.fetch(vestingPda);
+    console.log('Vesting:', {
+      unclaimed: vesting.unclaimed.toString(),
+      amount: vesting.amount.toString(),
+      claimed: vesting.claimed.toString(),
+      endSlot: vesting.endSlot.toString(),
+    });
+
+    try {
+      await program.methods
+        .claimVested()
+        .accounts({ vesting: vestingPda, user: pda(Buffer.from("user"), payer.toBuffer()), payer })
+        .rpc();
+      const updated = await program.account.vesting.fetch(vestingPda);
+      assert.isTrue(updated.unclaimed.isZero());
+    } catch (err) {
+      assert.include(`${err}`, "NothingVested");
+    }
+  });
+
+  it('Vests a win for a player who never opened a vesting account', async () => {
+    const config = await program.account.vestingConfig.fetch(vestingConfigPda);
+
+    // Vest every win in full for this round; the winner never initialized a vesting account, the round creates it
+    await program.methods
+      .setVestingConfig(new anchor.BN(0), 0, config.vestingSlots)
+      .accounts({ vestingConfig: vestingConfigPda, admin: payer })
+      .rpc();
+    try {
+      await fundPlayer(program, winner);
+      await winProofRound(program, winner);
+    } finally {
+      await program.methods
+        .setVestingConfig(config.thresholdLamports, config.immediateBps, config.vestingSlots)
+        .accounts({ vestingConfig: vestingConfigPda, admin: payer })
+        .rpc();
+    }
+    const vesting = await program.account.vesting.fetch(winnerVestingPda);
+    assert.isTrue(vesting.beneficiary.equals(winner.publicKey));
+    assert.isTrue(vesting.amount.gtn(0));
+  });
+});
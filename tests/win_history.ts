@@ -0,0 +1,83 @@
+import * as anchor from '@coral-xyz/anchor';
+import { assert } from 'chai';
+import { PublicKey, ComputeBudgetProgram, SystemProgram } from '@solana/web3.js';
+import { recentWins } from '../app/accounts';
+
+describe('prime_slot_checker_win_history', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PrimeSlotChecker as anchor.Program<any>;
+  const payer = provider.wallet.publicKey;
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+  const winHistoryPda = pda(Buffer.from("win_history"));
+  const userPda = pda(Buffer.from("user"), payer.toBuffer());
+
+  it('Keeps the most recent wins newest first', async () => {
+    const winHistory = await program.account.winHistory.fetch(winHistoryPda);
+    assert.equal(winHistory.version, 1);
+    assert.isAtMost(winHistory.count, 10);
+
+    const wins = recentWins(winHistory);
+    for (let i = 1; i < wins.length; i++) {
+      assert.isTrue(wins[i - 1].slot.gte(wins[i].slot));
+    }
+    for (const win of wins) {
+      assert.include([0, 1], win.kind);
+      assert.isAbove(win.powerUpBps, 0);
+    }
+    console.log('Recent wins:', wins.map((win) => ({
+      winner: win.winner.toBase58(),
+      slot: win.slot.toString(),
+      number: win.number.toString(),
+      lamports: win.lamports.toString(),
+      points: win.points.toString(),
+      kind: win.kind === 1 ? 'superprime' : 'prime',
+    })));
+  });
+
+  it('Records a winning check_slot as the newest entry', async () => {
+    const epochStatePda = pda(Buffer.from("epoch_state"));
+    const epochState = await program.account.epochState.fetch(epochStatePda);
+    const seasonStatePda = pda(Buffer.from("season_state"));
+    const seasonState = await program.account.seasonState.fetch(seasonStatePda);
+    const before = await program.account.user.fetch(userPda);
+
+    await program.methods
+      .checkSlot(0)
+      .accounts({
+        user: userPda,
+        jackpot: pda(Buffer.from("jackpot")),
+        treasury: pda(Buffer.from("treasury")),
+        stakingTreasury: pda(Buffer.from("staking_treasury")),
+        totalWonPoints: pda(Buffer.from("total_won_points")),
+        playerList: pda(Buffer.from("player_list")),
+        difficulty: pda(Buffer.from("difficulty")),
+        epochState: epochStatePda,
+        epoch: pda(Buffer.from("epoch"), epochState.currentEpoch.toArrayLike(Buffer, "le", 8)),
+        yieldAccumulator: pda(Buffer.from("yield_accumulator")),
+        vestingConfig: pda(Buffer.from("vesting_config")),
+        vesting: pda(Buffer.from("vesting"), payer.toBuffer()),
+        feeVault: pda(Buffer.from("fee_vault")),
+        gameStats: pda(Buffer.from("game_stats")),
+        winHistory: winHistoryPda,
+        seasonState: seasonStatePda,
+        season: pda(Buffer.from("season"), seasonState.currentSeason.toArrayLike(Buffer, "le", 8)),
+        payer,
+        systemProgram: SystemProgram.programId,
+      })
+      .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 300000 })])
+      .rpc();
+
+    const after = await program.account.user.fetch(userPda);
+    if (after.wins.toNumber() === before.wins.toNumber()) {
+      console.log('Slot was not a prime, nothing recorded');
+      return;
+    }
+    const newest = recentWins(await program.account.winHistory.fetch(winHistoryPda))[0];
+    assert.isTrue(newest.winner.equals(payer));
+    assert.equal(newest.slot.toString(), after.lastPlaySlot.toString());
+    assert.equal(newest.lamports.toString(), after.lamportsWon.sub(before.lamportsWon).toString());
+  });
+});